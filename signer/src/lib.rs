@@ -0,0 +1,197 @@
+//! Offline signer for the SocketFi user wallet's BLS owner scheme.
+//!
+//! Mirrors what the contract verifies: min-sig BLS12-381 with public keys in G1
+//! (96 byte uncompressed) and signatures in G2 (192 byte uncompressed), messages
+//! hashed to G2 with the `BLS12381G2_XMD:SHA-256_SSWU_RO_` suite under the wallet's DST.
+//! The cross-checks against `check_auth` live in the contract's `src/test.rs`.
+
+use bls12_381::{
+    hash_to_curve::{ExpandMsgXmd, HashToCurve},
+    G1Affine, G1Projective, G2Affine, G2Projective, Scalar,
+};
+use ff::Field;
+use group::Curve;
+use rand_core::OsRng;
+use sha2::{Digest, Sha256, Sha512};
+use stellar_xdr::curr::{Limits, ScAddress, ScSymbol, ScVal, ScVec, WriteXdr};
+
+/// DST of wallets that haven't migrated past version 1.
+pub const LEGACY_AUTH_DST: &[u8] = b"BLS_AUTH_XMD:SHA-256_SSWU_SOCKETFI";
+/// DST version written by the current contract, see `get_dst`.
+pub const AUTH_DST_VERSION: u32 = 2;
+pub const POP_DST: &[u8] = b"BLS_POP_XMD:SHA-256_SSWU_SOCKETFI";
+
+#[derive(Debug)]
+pub enum SignerError {
+    EmptyKeySet,
+    InvalidSymbol,
+    Xdr(stellar_xdr::curr::Error),
+}
+
+impl From<stellar_xdr::curr::Error> for SignerError {
+    fn from(e: stellar_xdr::curr::Error) -> Self {
+        SignerError::Xdr(e)
+    }
+}
+
+/// The auth DST a wallet uses at `version` on the network with `network_id`.
+pub fn auth_dst(version: u32, network_id: &[u8; 32]) -> Vec<u8> {
+    let mut dst = LEGACY_AUTH_DST.to_vec();
+    if version < 2 {
+        return dst;
+    }
+    dst.extend_from_slice(format!("_V{}_", version).as_bytes());
+    for byte in network_id {
+        dst.extend_from_slice(format!("{:02x}", byte).as_bytes());
+    }
+    dst
+}
+
+#[derive(Clone)]
+pub struct SecretKey(Scalar);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PublicKey(G1Affine);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Signature(G2Affine);
+
+impl SecretKey {
+    pub fn random() -> Self {
+        SecretKey(Scalar::random(OsRng))
+    }
+
+    /// Deterministic key from a seed, reduced from SHA-512(seed).
+    pub fn from_seed(seed: &[u8]) -> Self {
+        let mut wide = [0u8; 64];
+        wide.copy_from_slice(&Sha512::digest(seed));
+        SecretKey(Scalar::from_bytes_wide(&wide))
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey((G1Projective::generator() * self.0).to_affine())
+    }
+
+    pub fn sign(&self, msg: &[u8], dst: &[u8]) -> Signature {
+        let msg_g2 = <G2Projective as HashToCurve<ExpandMsgXmd<Sha256>>>::hash_to_curve(msg, dst);
+        Signature((msg_g2 * self.0).to_affine())
+    }
+
+    /// Signs a wallet auth payload under the wallet's DST, see [`auth_dst`].
+    pub fn sign_payload(&self, payload: &[u8; 32], dst: &[u8]) -> Signature {
+        self.sign(payload, dst)
+    }
+
+    /// Proof of possession over the serialized public key, required by `init`
+    /// and every key update.
+    pub fn proof_of_possession(&self) -> Signature {
+        self.sign(&self.public_key().to_bytes(), POP_DST)
+    }
+}
+
+impl PublicKey {
+    /// The 96 byte form `write_aggregated_pk_bytes` takes.
+    pub fn to_bytes(&self) -> [u8; 96] {
+        self.0.to_uncompressed()
+    }
+
+    pub fn from_bytes(bytes: &[u8; 96]) -> Option<Self> {
+        Option::from(G1Affine::from_uncompressed(bytes)).map(PublicKey)
+    }
+}
+
+impl Signature {
+    /// The 192 byte form carried in `BlsSignature::signature`.
+    pub fn to_bytes(&self) -> [u8; 192] {
+        self.0.to_uncompressed()
+    }
+
+    pub fn from_bytes(bytes: &[u8; 192]) -> Option<Self> {
+        Option::from(G2Affine::from_uncompressed(bytes)).map(Signature)
+    }
+}
+
+pub fn aggregate_pubkeys(pubkeys: &[PublicKey]) -> Result<PublicKey, SignerError> {
+    if pubkeys.is_empty() {
+        return Err(SignerError::EmptyKeySet);
+    }
+    let sum = pubkeys
+        .iter()
+        .fold(G1Projective::identity(), |acc, pk| acc + pk.0);
+    Ok(PublicKey(sum.to_affine()))
+}
+
+pub fn aggregate_signatures(signatures: &[Signature]) -> Result<Signature, SignerError> {
+    if signatures.is_empty() {
+        return Err(SignerError::EmptyKeySet);
+    }
+    let sum = signatures
+        .iter()
+        .fold(G2Projective::identity(), |acc, sig| acc + sig.0);
+    Ok(Signature(sum.to_affine()))
+}
+
+/// Bitmap for `BlsSignature::signers`, indexes follow the order keys were registered in.
+pub fn signer_bitmap(indexes: &[u32]) -> u32 {
+    indexes.iter().fold(0, |bitmap, i| bitmap | (1 << i))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValidityKind {
+    Ledger = 0,
+    Timestamp = 1,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ValidityWindow {
+    pub kind: ValidityKind,
+    pub valid_after: u64,
+    pub valid_until: u64,
+}
+
+/// Everything the contract hashes into the message for an owner entrypoint.
+pub struct AuthPayload {
+    pub channel: u32,
+    pub nonce: u64,
+    /// Aggregate of the wallet's full key set, as returned by summing `get_bls_signers`
+    pub aggregated_pubkey: PublicKey,
+    pub wallet: ScAddress,
+    /// SHA-256 of the network passphrase
+    pub network_id: [u8; 32],
+    pub fn_name: String,
+    /// Entrypoint arguments in order, without `tx_signature`
+    pub args: Vec<ScVal>,
+    pub validity: Option<ValidityWindow>,
+}
+
+impl AuthPayload {
+    /// sha256(channel || nonce || aggregated pubkey || wallet XDR || network id
+    ///        || fn_name XDR || args XDR [|| kind || valid_after || valid_until])
+    pub fn hash(&self) -> Result<[u8; 32], SignerError> {
+        let fn_name = ScSymbol(
+            self.fn_name
+                .as_bytes()
+                .to_vec()
+                .try_into()
+                .map_err(|_| SignerError::InvalidSymbol)?,
+        );
+        let args = ScVec(self.args.clone().try_into()?);
+
+        let mut hasher = Sha256::new();
+        hasher.update(self.channel.to_be_bytes());
+        hasher.update(self.nonce.to_be_bytes());
+        hasher.update(self.aggregated_pubkey.to_bytes());
+        hasher.update(ScVal::Address(self.wallet.clone()).to_xdr(Limits::none())?);
+        hasher.update(self.network_id);
+        hasher.update(ScVal::Symbol(fn_name).to_xdr(Limits::none())?);
+        hasher.update(ScVal::Vec(Some(args)).to_xdr(Limits::none())?);
+        if let Some(validity) = self.validity {
+            hasher.update((validity.kind as u32).to_be_bytes());
+            hasher.update(validity.valid_after.to_be_bytes());
+            hasher.update(validity.valid_until.to_be_bytes());
+        }
+        let mut hash = [0u8; 32];
+        hash.copy_from_slice(&hasher.finalize());
+        Ok(hash)
+    }
+}
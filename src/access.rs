@@ -1,12 +1,16 @@
-use soroban_sdk::{crypto::bls12_381::G1Affine, Address, BytesN, Env, String, Vec};
+use soroban_sdk::{crypto::bls12_381::G1Affine, Address, Bytes, BytesN, Env, Map, String, Vec};
 
 use crate::{
+    bls_account_auth::verify_bls_pop,
     data::DataKey,
     error::ContractError,
     formatter::{convert_to_lower, convert_to_upper, to_lower_bytes},
-    types::WebKeyDetails,
+    types::PasskeyDetails,
+    webauthn_account_auth::cose_to_sec1_pubkey,
 };
 
+const MAX_WEB_KEYS: u32 = 10;
+
 pub fn has_user_account(e: &Env) -> bool {
     let key = DataKey::UserAccountId;
     e.storage().instance().has(&key)
@@ -35,60 +39,185 @@ pub fn is_initialized(e: &Env) -> bool {
     e.storage().persistent().has(&key)
 }
 
-pub fn write_aggregated_pk_bytes(env: &Env, keypair_pubkeys: Vec<BytesN<96>>) {
-    let bls = env.crypto().bls12_381();
+pub fn check_bls_key_set(
+    env: &Env,
+    keypair_pubkeys: &Vec<BytesN<96>>,
+    keypair_pops: &Vec<BytesN<192>>,
+) -> Result<(), ContractError> {
+    if keypair_pubkeys.is_empty() {
+        return Err(ContractError::InvalidBlsPublicKey);
+    }
+    if keypair_pubkeys.len() != keypair_pops.len() {
+        return Err(ContractError::InvalidProofOfPossession);
+    }
+    // Signers are addressed through a u32 bitmap
+    if keypair_pubkeys.len() > 32 {
+        return Err(ContractError::TooManySigners);
+    }
+
+    // Every key must prove possession before it is summed, otherwise a rogue key
+    // chosen to cancel out the others could sign for the whole set alone
+    for i in 0..keypair_pubkeys.len() {
+        verify_bls_pop(
+            env,
+            &keypair_pubkeys.get_unchecked(i),
+            keypair_pops.get_unchecked(i),
+        )?;
+    }
+    Ok(())
+}
 
-    let mut keypair_1_array = [0u8; 96];
-    keypair_pubkeys
-        .get_unchecked(0)
-        .copy_into_slice(&mut keypair_1_array);
+pub fn write_aggregated_pk_bytes(
+    env: &Env,
+    keypair_pubkeys: Vec<BytesN<96>>,
+    keypair_pops: Vec<BytesN<192>>,
+) -> Result<(), ContractError> {
+    check_bls_key_set(env, &keypair_pubkeys, &keypair_pops)?;
+    write_bls_pubkeys(env, keypair_pubkeys);
+    Ok(())
+}
 
-    let mut agg_pk = G1Affine::from_bytes(BytesN::from_array(env, &keypair_1_array));
+// Stores the key set and its aggregate, the keys must have passed check_bls_key_set
+pub fn write_bls_pubkeys(env: &Env, keypair_pubkeys: Vec<BytesN<96>>) {
+    let bls = env.crypto().bls12_381();
 
-    for i in 1..keypair_pubkeys.len() {
-        let mut keypair_i_array = [0u8; 96];
-        keypair_pubkeys
-            .get_unchecked(i)
-            .copy_into_slice(&mut keypair_i_array);
+    let mut agg_pk = G1Affine::from_bytes(keypair_pubkeys.get_unchecked(0));
 
-        let pk = G1Affine::from_bytes(BytesN::from_array(env, &keypair_i_array));
+    for i in 1..keypair_pubkeys.len() {
+        let pk = G1Affine::from_bytes(keypair_pubkeys.get_unchecked(i));
         agg_pk = bls.g1_add(&agg_pk, &pk);
     }
 
     env.storage()
         .persistent()
         .set(&DataKey::BlsPublicKey, &agg_pk.to_bytes());
+    env.storage()
+        .persistent()
+        .set(&DataKey::BlsSigners, &keypair_pubkeys);
 }
 
 pub fn read_aggregated_pk_bytes(env: &Env) -> Option<BytesN<96>> {
     env.storage().persistent().get(&DataKey::BlsPublicKey)
 }
 
-pub fn write_web_keys_bytes(
-    env: &Env,
-    platform: String,
-    social_username: String,
-    web_pubkey: BytesN<77>,
-) {
-    let mut web_keys = read_web_keys_bytes(env);
+pub fn read_bls_pubkeys(env: &Env) -> Vec<BytesN<96>> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::BlsSigners)
+        .unwrap_or(Vec::new(env))
+}
 
-    web_keys.primary_social_acct = convert_to_lower(&env, social_username);
-    web_keys.platform = convert_to_lower(&env, platform);
+pub fn write_bls_threshold(env: &Env, threshold: u32) -> Result<(), ContractError> {
+    if threshold == 0 || threshold > read_bls_pubkeys(env).len() {
+        return Err(ContractError::InvalidThreshold);
+    }
     env.storage()
         .persistent()
-        .set(&DataKey::WebKey, &web_pubkey);
+        .set(&DataKey::BlsThreshold, &threshold);
+    Ok(())
 }
 
-pub fn read_web_keys_bytes(env: &Env) -> WebKeyDetails {
-    let default_webkeys = WebKeyDetails {
-        web_public_key: BytesN::from_array(&env, &[0u8; 77]),
-        primary_social_acct: String::from_str(&env, ""),
-        platform: String::from_str(&env, ""),
-    };
+pub fn read_bls_threshold(env: &Env) -> u32 {
     env.storage()
+        .persistent()
+        .get(&DataKey::BlsThreshold)
+        .unwrap_or(read_bls_pubkeys(env).len())
+}
+
+pub fn write_social_profile(env: &Env, social_username: String) {
+    env.storage().persistent().set(
+        &DataKey::PrimarySocialProfile,
+        &convert_to_lower(env, social_username),
+    );
+}
+
+pub fn read_social_profile(env: &Env) -> String {
+    env.storage()
+        .persistent()
+        .get(&DataKey::PrimarySocialProfile)
+        .unwrap_or(String::from_str(env, ""))
+}
+
+pub fn read_web_keys(env: &Env) -> Map<Bytes, PasskeyDetails> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::WebKeys)
+        .unwrap_or(Map::new(env))
+}
+
+pub fn read_web_key(env: &Env, credential_id: &Bytes) -> Option<PasskeyDetails> {
+    read_web_keys(env).get(credential_id.clone())
+}
+
+pub fn write_add_web_key(
+    env: &Env,
+    credential_id: Bytes,
+    web_pubkey: BytesN<77>,
+    label: String,
+    platform: String,
+) -> Result<(), ContractError> {
+    let mut web_keys = read_web_keys(env);
+    if web_keys.contains_key(credential_id.clone()) {
+        return Err(ContractError::WebKeyAlreadyExists);
+    }
+    if web_keys.len() >= MAX_WEB_KEYS {
+        return Err(ContractError::TooManyWebKeys);
+    }
+    // Reject keys the WebAuthn verifier would not be able to use
+    cose_to_sec1_pubkey(env, &web_pubkey)?;
+
+    let details = PasskeyDetails {
+        credential_id: credential_id.clone(),
+        web_public_key: web_pubkey,
+        label,
+        platform: convert_to_lower(env, platform),
+        created_ledger: env.ledger().sequence(),
+        last_used_ledger: 0,
+    };
+    web_keys.set(credential_id, details);
+    env.storage().persistent().set(&DataKey::WebKeys, &web_keys);
+    Ok(())
+}
+
+// Moves the passkey a pre-registry wallet kept under DataKey::WebKey into the registry.
+// The old layout never stored the credential id, so the owner supplies it.
+pub fn migrate_legacy_web_key(
+    env: &Env,
+    credential_id: Bytes,
+    platform: String,
+) -> Result<(), ContractError> {
+    let web_pubkey: BytesN<77> = env
+        .storage()
         .persistent()
         .get(&DataKey::WebKey)
-        .unwrap_or(default_webkeys)
+        .ok_or(ContractError::WebKeyNotFound)?;
+    write_add_web_key(
+        env,
+        credential_id,
+        web_pubkey,
+        String::from_str(env, "primary"),
+        platform,
+    )?;
+    env.storage().persistent().remove(&DataKey::WebKey);
+    Ok(())
+}
+
+pub fn write_remove_web_key(env: &Env, credential_id: Bytes) -> Result<(), ContractError> {
+    let mut web_keys = read_web_keys(env);
+    if web_keys.remove(credential_id).is_none() {
+        return Err(ContractError::WebKeyNotFound);
+    }
+    env.storage().persistent().set(&DataKey::WebKeys, &web_keys);
+    Ok(())
+}
+
+pub fn write_web_key_used(env: &Env, credential_id: Bytes) {
+    let mut web_keys = read_web_keys(env);
+    if let Some(mut details) = web_keys.get(credential_id.clone()) {
+        details.last_used_ledger = env.ledger().sequence();
+        web_keys.set(credential_id, details);
+        env.storage().persistent().set(&DataKey::WebKeys, &web_keys);
+    }
 }
 
 pub fn read_master_contract_id(e: &Env) -> Option<Address> {
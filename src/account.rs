@@ -1,21 +1,65 @@
-use soroban_sdk::{contract, contractimpl, token, Address, BytesN, Env, Map, String, Vec};
+use soroban_sdk::{
+    auth::{Context, CustomAccountInterface, InvokerContractAuthEntry},
+    contract, contractimpl,
+    crypto::Hash,
+    vec, Address, Bytes, BytesN, Env, IntoVal, Map, String, Symbol, Val, Vec,
+};
 
 use crate::{
     access::{
-        is_initialized, read_dapp_router_contract_id, read_master_contract_id, read_user_account,
-        read_web_keys_bytes, write_aggregated_pk_bytes, write_dapp_router_contract_id,
-        write_master_contract_id, write_user_account, write_web_keys_bytes,
+        is_initialized, migrate_legacy_web_key, read_bls_pubkeys, read_bls_threshold,
+        read_dapp_router_contract_id, read_social_profile, read_user_account, read_web_keys,
+        write_add_web_key, write_aggregated_pk_bytes, write_remove_web_key,
     },
     account_token::{
-        read_allowance, read_allowance_expiration, read_balance, send_token, spend_token,
-        take_token, write_allowance_expiration, write_approve,
+        read_allowance, read_allowance_expiration, read_balance, spend_token, take_token,
+        write_allowance_expiration,
+    },
+    address_book::{
+        read_address_book, read_allowlist_mode, require_allowed_destination,
+        write_address_book_entry, write_allowlist_mode, write_remove_address_book_entry,
+    },
+    admin_changes::{
+        cancel_pending_admin_change, execute_pending_admin_change, queue_admin_change,
+        read_admin_change_delay, read_pending_changes,
+    },
+    auth_policy::{read_auth_policies, write_auth_policy, write_remove_auth_policy},
+    bls_account_auth::{
+        check_contract_auth, migrate_dst, owner_require_auth, owner_require_batch_auth,
+        read_dst_bytes, read_nonce, write_dst_bytes, AuthMethod,
     },
-    bls_account_auth::{owner_require_auth, read_nonce, write_dst_bytes, write_nonce},
     constructor::init_constructor,
-    dap_adapter::{self, deep_auth_aqua_amm, deep_auth_soroswap},
+    dual_control::{read_dual_control, write_dual_control},
+    ed25519_signers::{
+        read_ed25519_signers, write_add_ed25519_signer, write_remove_ed25519_signer,
+    },
     error::ContractError,
-    token_list::{read_token_list, write_add_token, write_remove_token},
-    types::{AllowanceDetails, TokenDetails, WebKeyDetails},
+    evm_signers::{read_evm_signers, write_add_evm_signer, write_remove_evm_signer},
+    freeze::{self, execute_unfreeze, read_freeze_state, require_not_frozen, write_freeze},
+    guardians::{self, read_guardian_config, read_recovery, write_guardian_config},
+    key_rotation::{
+        cancel_pending_key_rotation, execute_pending_key_rotation, queue_key_rotation,
+        read_pending_key_rotation, write_key_rotation_delay,
+    },
+    operations::{
+        batch_op_fn, enforce_invocation_limits, is_wallet_outflow, run_add_token, run_approve,
+        run_batch, run_invoke, run_swap_aqua, run_swap_soroswap, run_withdraw,
+    },
+    session_keys::{read_session_keys, write_add_session_key, write_revoke_session_key},
+    spend_limits::{
+        is_looser_limit, read_token_limit, require_elevated_auth, write_remove_token_limit,
+        write_token_limit,
+    },
+    token_list::{
+        read_hidden_tokens, read_portfolio, read_token_list, read_token_metadata, write_add_token,
+        write_backfill_token_metadata, write_remove_token, write_token_hidden,
+    },
+    types::{
+        AddressBookEntry, AdminChange, AllowanceDetails, AuthPolicy, BatchOp, BatchResult,
+        Ed25519Signer, EvmSigner, FreezeState, Guardian, GuardianConfig, GuardianProof,
+        PasskeyDetails, PendingAdminChange, PendingKeyRotation, Portfolio, RecoveryRequest,
+        SessionKey, SignerProof, TokenDetails, TokenLimit, TokenMetadata, TxSignature,
+    },
     user_account_trait::AccountTrait,
 };
 
@@ -29,9 +73,12 @@ impl AccountTrait for Account {
     fn init(
         env: Env,
         bls_pubkeys: Vec<BytesN<96>>,
+        bls_pops: Vec<BytesN<192>>,
+        bls_threshold: u32,
         platform: String,
         social_username: String,
         web_pubkey: BytesN<77>,
+        web_credential_id: Bytes,
         master_contract_id: Address,
         dapp_router_contract_id: Address,
     ) -> Result<(), ContractError> {
@@ -41,9 +88,12 @@ impl AccountTrait for Account {
         init_constructor(
             env,
             bls_pubkeys,
+            bls_pops,
+            bls_threshold,
             platform,
             social_username,
             web_pubkey,
+            web_credential_id,
             master_contract_id,
             dapp_router_contract_id,
         )?;
@@ -53,9 +103,14 @@ impl AccountTrait for Account {
     fn update_allowance_expiration(
         env: Env,
         expiration_ledger: u32,
-        tx_signature: Option<BytesN<192>>,
+        tx_signature: Option<TxSignature>,
     ) -> Result<(), ContractError> {
-        owner_require_auth(env.clone(), tx_signature)?;
+        owner_require_auth(
+            env.clone(),
+            Symbol::new(&env, "update_allowance_expiration"),
+            (expiration_ledger,).into_val(&env),
+            tx_signature,
+        )?;
 
         write_allowance_expiration(&env, expiration_ledger);
         Ok(())
@@ -64,32 +119,44 @@ impl AccountTrait for Account {
     fn set_user_account(
         env: Env,
         user_account_id: Address,
-        tx_signature: Option<BytesN<192>>,
+        tx_signature: Option<TxSignature>,
     ) -> Result<(), ContractError> {
-        owner_require_auth(env.clone(), tx_signature)?;
+        owner_require_auth(
+            env.clone(),
+            Symbol::new(&env, "set_user_account"),
+            (user_account_id.clone(),).into_val(&env),
+            tx_signature,
+        )?;
 
-        write_user_account(&env, &user_account_id);
-        Ok(())
+        queue_admin_change(&env, AdminChange::UserAccount(user_account_id))
     }
     ///Update Master Wallet Contract
     fn update_master_contract(
         env: Env,
         master_contract_id: Address,
-        tx_signature: Option<BytesN<192>>,
+        tx_signature: Option<TxSignature>,
     ) -> Result<(), ContractError> {
-        owner_require_auth(env.clone(), tx_signature)?;
-        write_master_contract_id(&env, &master_contract_id);
-        Ok(())
+        owner_require_auth(
+            env.clone(),
+            Symbol::new(&env, "update_master_contract"),
+            (master_contract_id.clone(),).into_val(&env),
+            tx_signature,
+        )?;
+        queue_admin_change(&env, AdminChange::MasterContract(master_contract_id))
     }
     ///Update dApp Router Contract
     fn update_dapp_router_contract(
         env: Env,
         dapp_router_contract_id: Address,
-        tx_signature: Option<BytesN<192>>,
+        tx_signature: Option<TxSignature>,
     ) -> Result<(), ContractError> {
-        owner_require_auth(env.clone(), tx_signature)?;
-        write_dapp_router_contract_id(&env, &dapp_router_contract_id);
-        Ok(())
+        owner_require_auth(
+            env.clone(),
+            Symbol::new(&env, "update_dapp_router_contract"),
+            (dapp_router_contract_id.clone(),).into_val(&env),
+            tx_signature,
+        )?;
+        queue_admin_change(&env, AdminChange::DappRouter(dapp_router_contract_id))
     }
     ///Deposit Tokens
     fn deposit(
@@ -111,12 +178,18 @@ impl AccountTrait for Account {
         to: Address,
         token_id: Address,
         amount: i128,
-        tx_signature: Option<BytesN<192>>,
+        tx_signature: Option<TxSignature>,
     ) -> Result<(), ContractError> {
-        owner_require_auth(env.clone(), tx_signature)?;
+        require_not_frozen(&env)?;
 
-        send_token(&env, &to, &token_id, amount);
-        Ok(())
+        let auth = owner_require_auth(
+            env.clone(),
+            Symbol::new(&env, "withdraw"),
+            (to.clone(), token_id.clone(), amount).into_val(&env),
+            tx_signature,
+        )?;
+
+        run_withdraw(&env, &auth, to, token_id, amount)
     }
 
     ///Swap on Soroswap
@@ -126,41 +199,18 @@ impl AccountTrait for Account {
         amount_out_min: i128,
         path: Vec<Address>,
         deadline: u64,
-        tx_signature: Option<BytesN<192>>,
+        tx_signature: Option<TxSignature>,
     ) -> Result<(), ContractError> {
-        owner_require_auth(env.clone(), tx_signature)?;
-
-        let to = env.current_contract_address();
-
-        let dapp_adapter_id = read_master_contract_id(&env).unwrap();
-        let dapp_adapter_contract = dap_adapter::Client::new(&env, &dapp_adapter_id);
-
-        let soroswap_id = dapp_adapter_contract.get_soroswap_id();
-
-        let pair_id = dapp_adapter_contract
-            .get_pair_router_soroswap(&path.get_unchecked(0), &path.get_unchecked(1));
+        require_not_frozen(&env)?;
 
-        deep_auth_soroswap(
+        let auth = owner_require_auth(
             env.clone(),
-            soroswap_id,
-            pair_id,
-            amount_in.clone(),
-            amount_out_min.clone(),
-            deadline.clone(),
-            path.clone(),
-            to.clone(),
-        );
-
-        dapp_adapter_contract.swap_exact_soroswap(
-            &amount_in,
-            &amount_out_min,
-            &path,
-            &to,
-            &deadline,
-        );
-
-        write_add_token(env, path.get_unchecked(1));
+            Symbol::new(&env, "swap_tokens_soroswap"),
+            (amount_in, amount_out_min, path.clone(), deadline).into_val(&env),
+            tx_signature,
+        )?;
 
+        run_swap_soroswap(&env, &auth, amount_in, amount_out_min, path, deadline)?;
         Ok(())
     }
 
@@ -171,29 +221,18 @@ impl AccountTrait for Account {
         token_in: Address,
         in_amount: u128,
         out_min: u128,
-        tx_signature: Option<BytesN<192>>,
+        tx_signature: Option<TxSignature>,
     ) -> Result<(), ContractError> {
-        owner_require_auth(env.clone(), tx_signature)?;
-
-        let to = env.current_contract_address();
-
-        let dapp_adapter_id = read_master_contract_id(&env).unwrap();
-        let dapp_adapter_contract = dap_adapter::Client::new(&env, &dapp_adapter_id);
+        require_not_frozen(&env)?;
 
-        let aqua_router_id = dapp_adapter_contract.get_aqua_amm_router_id();
-
-        deep_auth_aqua_amm(
-            env,
-            aqua_router_id.clone(),
-            to.clone(),
-            swaps_chain.clone(),
-            token_in.clone(),
-            in_amount.clone(),
-            out_min.clone(),
-        );
-
-        dapp_adapter_contract.swap_chain_aqua(&to, &swaps_chain, &token_in, &in_amount, &out_min);
+        let auth = owner_require_auth(
+            env.clone(),
+            Symbol::new(&env, "swap_tokens_aqua"),
+            (swaps_chain.clone(), token_in.clone(), in_amount, out_min).into_val(&env),
+            tx_signature,
+        )?;
 
+        run_swap_aqua(&env, &auth, swaps_chain, token_in, in_amount, out_min)?;
         Ok(())
     }
 
@@ -203,12 +242,67 @@ impl AccountTrait for Account {
         token_id: Address,
         spender: Address,
         amount: i128,
-        tx_signature: Option<BytesN<192>>,
+        tx_signature: Option<TxSignature>,
     ) -> Result<(), ContractError> {
-        owner_require_auth(env.clone(), tx_signature)?;
+        require_not_frozen(&env)?;
 
-        write_approve(&env, &token_id, &spender, &amount);
-        Ok(())
+        let auth = owner_require_auth(
+            env.clone(),
+            Symbol::new(&env, "approve"),
+            (token_id.clone(), spender.clone(), amount).into_val(&env),
+            tx_signature,
+        )?;
+
+        run_approve(&env, &auth, token_id, spender, amount)
+    }
+
+    ///Execute Batch of Operations
+    fn execute_batch(
+        env: Env,
+        ops: Vec<BatchOp>,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<Vec<BatchResult>, ContractError> {
+        let mut op_fns = Vec::new(&env);
+        for op in ops.iter() {
+            op_fns.push_back(batch_op_fn(&env, &op));
+        }
+
+        let auth = owner_require_batch_auth(
+            env.clone(),
+            Symbol::new(&env, "execute_batch"),
+            op_fns,
+            (ops.clone(),).into_val(&env),
+            tx_signature,
+        )?;
+
+        run_batch(&env, &auth, ops)
+    }
+
+    ///Invoke Contract As Wallet
+    fn invoke(
+        env: Env,
+        contract: Address,
+        fn_name: Symbol,
+        args: Vec<Val>,
+        auth_entries: Vec<InvokerContractAuthEntry>,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<Val, ContractError> {
+        require_not_frozen(&env)?;
+
+        let auth = owner_require_auth(
+            env.clone(),
+            Symbol::new(&env, "invoke"),
+            (
+                contract.clone(),
+                fn_name.clone(),
+                args.clone(),
+                auth_entries.clone(),
+            )
+                .into_val(&env),
+            tx_signature,
+        )?;
+
+        run_invoke(&env, &auth, contract, fn_name, args, auth_entries)
     }
 
     ///Spend
@@ -220,22 +314,138 @@ impl AccountTrait for Account {
         to: Address,
     ) -> Result<(), ContractError> {
         spender.require_auth();
+        require_not_frozen(&env)?;
+        require_allowed_destination(&env, &to)?;
+        // The allowance was charged to the token limit when it was approved
         spend_token(&env, &spender, &token_id, amount, &to);
 
         Ok(())
     }
 
+    ///Set Token Spending Limit
+    fn set_token_limit(
+        env: Env,
+        token_id: Address,
+        per_tx: i128,
+        per_window: i128,
+        window_ledgers: u32,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError> {
+        let auth = owner_require_auth(
+            env.clone(),
+            Symbol::new(&env, "set_token_limit"),
+            (token_id.clone(), per_tx, per_window, window_ledgers).into_val(&env),
+            tx_signature,
+        )?;
+
+        let limit = TokenLimit {
+            per_tx,
+            per_window,
+            window_ledgers,
+        };
+        if let Some(current) = read_token_limit(&env, &token_id) {
+            if is_looser_limit(&current, &limit) {
+                require_elevated_auth(&env, &auth)?;
+            }
+        }
+        write_token_limit(&env, &token_id, &limit)
+    }
+
+    ///Remove Token Spending Limit
+    fn remove_token_limit(
+        env: Env,
+        token_id: Address,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError> {
+        let auth = owner_require_auth(
+            env.clone(),
+            Symbol::new(&env, "remove_token_limit"),
+            (token_id.clone(),).into_val(&env),
+            tx_signature,
+        )?;
+
+        if read_token_limit(&env, &token_id).is_some() {
+            require_elevated_auth(&env, &auth)?;
+        }
+        write_remove_token_limit(&env, &token_id);
+        Ok(())
+    }
+
+    ///Add Token To List
+    fn add_token(
+        env: Env,
+        token_id: Address,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError> {
+        owner_require_auth(
+            env.clone(),
+            Symbol::new(&env, "add_token"),
+            (token_id.clone(),).into_val(&env),
+            tx_signature,
+        )?;
+
+        run_add_token(&env, token_id)
+    }
+
+    ///Remove Token From List
+    fn remove_token(
+        env: Env,
+        token_id: Address,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError> {
+        owner_require_auth(
+            env.clone(),
+            Symbol::new(&env, "remove_token"),
+            (token_id.clone(),).into_val(&env),
+            tx_signature,
+        )?;
+
+        write_remove_token(env, token_id)
+    }
+
+    ///Hide or Show Token
+    fn set_token_hidden(
+        env: Env,
+        token_id: Address,
+        hidden: bool,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError> {
+        owner_require_auth(
+            env.clone(),
+            Symbol::new(&env, "set_token_hidden"),
+            (token_id.clone(), hidden).into_val(&env),
+            tx_signature,
+        )?;
+
+        write_token_hidden(&env, &token_id, hidden)
+    }
+
+    ///Get Token Metadata
+    fn get_token_metadata(env: Env, token_id: Address) -> TokenMetadata {
+        read_token_metadata(&env, &token_id)
+    }
+
+    ///Cache Legacy Token Metadata
+    fn backfill_token_metadata(env: Env, offset: u32, limit: u32) -> u32 {
+        write_backfill_token_metadata(&env, offset, limit)
+    }
+
     ///Get Token Details
     fn get_token_list(env: Env) -> Result<Map<Address, TokenDetails>, ContractError> {
         let mut token_details: Map<Address, TokenDetails> = Map::new(&env);
         let token_list = read_token_list(env.clone());
+        let hidden_tokens = read_hidden_tokens(&env);
 
         for i in 0..token_list.len() {
             let token_id = token_list.get_unchecked(i);
+            if hidden_tokens.contains_key(token_id.clone()) {
+                continue;
+            }
             let balance = read_balance(&env, &token_id);
             if balance > 0 {
+                let metadata = read_token_metadata(&env, &token_id);
                 let details = TokenDetails {
-                    symbol: token::Client::new(&env, &token_id).symbol(),
+                    symbol: metadata.symbol,
                     balance: balance,
                 };
                 token_details.set(token_id, details)
@@ -245,6 +455,17 @@ impl AccountTrait for Account {
         Ok(token_details)
     }
 
+    ///Get Portfolio Page
+    fn get_portfolio(
+        env: Env,
+        offset: u32,
+        limit: u32,
+        include_zero: bool,
+        include_hidden: bool,
+    ) -> Portfolio {
+        read_portfolio(&env, offset, limit, include_zero, include_hidden)
+    }
+
     ///Get Spender Allowances
     fn get_spender_allowances(
         env: Env,
@@ -258,7 +479,7 @@ impl AccountTrait for Account {
             let allowance = read_allowance(&env, &token_id, &spender);
             if allowance > 0 {
                 let details = AllowanceDetails {
-                    symbol: token::Client::new(&env, &token_id).symbol(),
+                    symbol: read_token_metadata(&env, &token_id).symbol,
                     allowance: allowance,
                 };
                 allowance_details.set(token_id, details)
@@ -268,9 +489,210 @@ impl AccountTrait for Account {
         Ok(allowance_details)
     }
 
-    ///Get Passkey
-    fn get_web_keys(env: Env) -> WebKeyDetails {
-        read_web_keys_bytes(&env)
+    ///Register Passkey
+    fn add_web_key(
+        env: Env,
+        credential_id: Bytes,
+        web_pubkey: BytesN<77>,
+        label: String,
+        platform: String,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError> {
+        owner_require_auth(
+            env.clone(),
+            Symbol::new(&env, "add_web_key"),
+            (
+                credential_id.clone(),
+                web_pubkey.clone(),
+                label.clone(),
+                platform.clone(),
+            )
+                .into_val(&env),
+            tx_signature,
+        )?;
+
+        write_add_web_key(&env, credential_id, web_pubkey, label, platform)
+    }
+
+    ///Remove Passkey
+    fn remove_web_key(
+        env: Env,
+        credential_id: Bytes,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError> {
+        owner_require_auth(
+            env.clone(),
+            Symbol::new(&env, "remove_web_key"),
+            (credential_id.clone(),).into_val(&env),
+            tx_signature,
+        )?;
+
+        write_remove_web_key(&env, credential_id)
+    }
+
+    ///Migrate Legacy Passkey
+    fn migrate_web_key(
+        env: Env,
+        credential_id: Bytes,
+        platform: String,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError> {
+        owner_require_auth(
+            env.clone(),
+            Symbol::new(&env, "migrate_web_key"),
+            (credential_id.clone(), platform.clone()).into_val(&env),
+            tx_signature,
+        )?;
+
+        migrate_legacy_web_key(&env, credential_id, platform)
+    }
+
+    ///Get Passkeys
+    fn get_web_keys(env: Env) -> Vec<PasskeyDetails> {
+        read_web_keys(&env).values()
+    }
+
+    ///Add Ed25519 Signer
+    fn add_ed25519_signer(
+        env: Env,
+        public_key: BytesN<32>,
+        label: String,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError> {
+        owner_require_auth(
+            env.clone(),
+            Symbol::new(&env, "add_ed25519_signer"),
+            (public_key.clone(), label.clone()).into_val(&env),
+            tx_signature,
+        )?;
+
+        write_add_ed25519_signer(&env, public_key, label)
+    }
+
+    ///Remove Ed25519 Signer
+    fn remove_ed25519_signer(
+        env: Env,
+        public_key: BytesN<32>,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError> {
+        owner_require_auth(
+            env.clone(),
+            Symbol::new(&env, "remove_ed25519_signer"),
+            (public_key.clone(),).into_val(&env),
+            tx_signature,
+        )?;
+
+        write_remove_ed25519_signer(&env, public_key)
+    }
+
+    ///Get Ed25519 Signers
+    fn get_ed25519_signers(env: Env) -> Vec<Ed25519Signer> {
+        read_ed25519_signers(&env).values()
+    }
+
+    ///Add EVM Signer
+    fn add_evm_signer(
+        env: Env,
+        address: BytesN<20>,
+        label: String,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError> {
+        owner_require_auth(
+            env.clone(),
+            Symbol::new(&env, "add_evm_signer"),
+            (address.clone(), label.clone()).into_val(&env),
+            tx_signature,
+        )?;
+
+        write_add_evm_signer(&env, address, label)
+    }
+
+    ///Remove EVM Signer
+    fn remove_evm_signer(
+        env: Env,
+        address: BytesN<20>,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError> {
+        owner_require_auth(
+            env.clone(),
+            Symbol::new(&env, "remove_evm_signer"),
+            (address.clone(),).into_val(&env),
+            tx_signature,
+        )?;
+
+        write_remove_evm_signer(&env, address)
+    }
+
+    ///Get EVM Signers
+    fn get_evm_signers(env: Env) -> Vec<EvmSigner> {
+        read_evm_signers(&env).values()
+    }
+
+    ///Set Address Book Entry
+    fn set_address_book_entry(
+        env: Env,
+        address: Address,
+        label: String,
+        trusted: bool,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError> {
+        owner_require_auth(
+            env.clone(),
+            Symbol::new(&env, "set_address_book_entry"),
+            (address.clone(), label.clone(), trusted).into_val(&env),
+            tx_signature,
+        )?;
+
+        write_address_book_entry(&env, address, label, trusted);
+        Ok(())
+    }
+
+    ///Remove Address Book Entry
+    fn remove_address_book_entry(
+        env: Env,
+        address: Address,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError> {
+        owner_require_auth(
+            env.clone(),
+            Symbol::new(&env, "remove_address_book_entry"),
+            (address.clone(),).into_val(&env),
+            tx_signature,
+        )?;
+
+        write_remove_address_book_entry(&env, address)
+    }
+
+    ///Get Address Book
+    fn get_address_book(env: Env) -> Vec<AddressBookEntry> {
+        read_address_book(&env).values()
+    }
+
+    ///Set Allowlist Mode
+    fn set_allowlist_mode(
+        env: Env,
+        enabled: bool,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError> {
+        owner_require_auth(
+            env.clone(),
+            Symbol::new(&env, "set_allowlist_mode"),
+            (enabled,).into_val(&env),
+            tx_signature,
+        )?;
+
+        write_allowlist_mode(&env, enabled);
+        Ok(())
+    }
+
+    ///Get Allowlist Mode
+    fn get_allowlist_mode(env: Env) -> bool {
+        read_allowlist_mode(&env)
+    }
+
+    ///Get Social Profile
+    fn get_social_profile(env: Env) -> String {
+        read_social_profile(&env)
     }
 
     ///Get Spender Allowance
@@ -279,8 +701,388 @@ impl AccountTrait for Account {
     }
 
     ///Get Current Nonce
-    fn get_nonce(env: Env) -> BytesN<32> {
-        read_nonce(&env)
+    fn get_nonce(env: Env, channel: u32) -> u64 {
+        read_nonce(&env, channel)
+    }
+
+    ///Register Session Key
+    fn add_session_key(
+        env: Env,
+        public_key: BytesN<32>,
+        expiry_ledger: u32,
+        allowed_fns: Vec<Symbol>,
+        allowed_tokens: Vec<Address>,
+        spend_cap: i128,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError> {
+        owner_require_auth(
+            env.clone(),
+            Symbol::new(&env, "add_session_key"),
+            (
+                public_key.clone(),
+                expiry_ledger,
+                allowed_fns.clone(),
+                allowed_tokens.clone(),
+                spend_cap,
+            )
+                .into_val(&env),
+            tx_signature,
+        )?;
+
+        write_add_session_key(
+            &env,
+            public_key,
+            expiry_ledger,
+            allowed_fns,
+            allowed_tokens,
+            spend_cap,
+        )
+    }
+
+    ///Revoke Session Key
+    fn revoke_session_key(
+        env: Env,
+        public_key: BytesN<32>,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError> {
+        owner_require_auth(
+            env.clone(),
+            Symbol::new(&env, "revoke_session_key"),
+            (public_key.clone(),).into_val(&env),
+            tx_signature,
+        )?;
+
+        write_revoke_session_key(&env, public_key)
+    }
+
+    ///Get Session Keys
+    fn get_session_keys(env: Env) -> Vec<SessionKey> {
+        read_session_keys(&env).values()
+    }
+
+    ///Rotate BLS Keys
+    fn rotate_bls_keys(
+        env: Env,
+        new_pubkeys: Vec<BytesN<96>>,
+        pops: Vec<BytesN<192>>,
+        threshold: u32,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError> {
+        let auth = owner_require_auth(
+            env.clone(),
+            Symbol::new(&env, "rotate_bls_keys"),
+            (new_pubkeys.clone(), pops.clone(), threshold).into_val(&env),
+            tx_signature,
+        )?;
+        // Only the current key set may replace itself
+        match auth {
+            AuthMethod::Bls | AuthMethod::BlsAndUserAccount => {}
+            _ => return Err(ContractError::BlsAuthRequired),
+        }
+
+        queue_key_rotation(&env, new_pubkeys, pops, threshold)
+    }
+
+    ///Execute Pending Key Rotation
+    fn execute_key_rotation(env: Env) -> Result<(), ContractError> {
+        execute_pending_key_rotation(&env)
+    }
+
+    ///Cancel Pending Key Rotation
+    fn cancel_key_rotation(
+        env: Env,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError> {
+        owner_require_auth(
+            env.clone(),
+            Symbol::new(&env, "cancel_key_rotation"),
+            Vec::new(&env),
+            tx_signature,
+        )?;
+
+        cancel_pending_key_rotation(&env)
+    }
+
+    ///Set Key Rotation Delay
+    fn set_key_rotation_delay(
+        env: Env,
+        delay_ledgers: u32,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError> {
+        owner_require_auth(
+            env.clone(),
+            Symbol::new(&env, "set_key_rotation_delay"),
+            (delay_ledgers,).into_val(&env),
+            tx_signature,
+        )?;
+
+        write_key_rotation_delay(&env, delay_ledgers);
+        Ok(())
+    }
+
+    ///Get Pending Key Rotation
+    fn get_pending_key_rotation(env: Env) -> Option<PendingKeyRotation> {
+        read_pending_key_rotation(&env)
+    }
+
+    ///Set Recovery Guardians
+    fn set_guardians(
+        env: Env,
+        guardians: Vec<Guardian>,
+        threshold: u32,
+        recovery_delay: u32,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError> {
+        let auth = owner_require_auth(
+            env.clone(),
+            Symbol::new(&env, "set_guardians"),
+            (guardians.clone(), threshold, recovery_delay).into_val(&env),
+            tx_signature,
+        )?;
+        // Guardians can replace the key set, so only the key set may appoint them
+        match auth {
+            AuthMethod::Bls | AuthMethod::BlsAndUserAccount => {}
+            _ => return Err(ContractError::BlsAuthRequired),
+        }
+
+        write_guardian_config(&env, guardians, threshold, recovery_delay)
+    }
+
+    ///Start Guardian Recovery
+    fn start_recovery(
+        env: Env,
+        guardian_index: u32,
+        proof: GuardianProof,
+        new_pubkeys: Vec<BytesN<96>>,
+        pops: Vec<BytesN<192>>,
+        threshold: u32,
+    ) -> Result<(), ContractError> {
+        guardians::start_recovery(&env, guardian_index, proof, new_pubkeys, pops, threshold)
+    }
+
+    ///Approve Guardian Recovery
+    fn approve_recovery(
+        env: Env,
+        guardian_index: u32,
+        proof: GuardianProof,
+    ) -> Result<(), ContractError> {
+        guardians::approve_recovery(&env, guardian_index, proof)
+    }
+
+    ///Finalize Guardian Recovery
+    fn finalize_recovery(env: Env) -> Result<(), ContractError> {
+        guardians::finalize_recovery(&env)
+    }
+
+    ///Cancel Guardian Recovery
+    fn cancel_recovery(env: Env, tx_signature: Option<TxSignature>) -> Result<(), ContractError> {
+        owner_require_auth(
+            env.clone(),
+            Symbol::new(&env, "cancel_recovery"),
+            Vec::new(&env),
+            tx_signature,
+        )?;
+
+        guardians::cancel_recovery(&env)
+    }
+
+    ///Get Recovery Guardians
+    fn get_guardians(env: Env) -> Option<GuardianConfig> {
+        read_guardian_config(&env)
+    }
+
+    ///Get Pending Recovery
+    fn get_recovery(env: Env) -> Option<RecoveryRequest> {
+        read_recovery(&env)
+    }
+
+    ///Migrate Signature DST
+    fn migrate_dst(
+        env: Env,
+        grace_ledgers: u32,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError> {
+        owner_require_auth(
+            env.clone(),
+            Symbol::new(&env, "migrate_dst"),
+            (grace_ledgers,).into_val(&env),
+            tx_signature,
+        )?;
+
+        migrate_dst(&env, grace_ledgers)
+    }
+
+    ///Get Signature DST
+    fn get_dst(env: Env) -> Bytes {
+        read_dst_bytes(&env).unwrap()
+    }
+
+    ///Set Function Auth Policy
+    fn set_auth_policy(
+        env: Env,
+        fn_name: Symbol,
+        policy: AuthPolicy,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError> {
+        // Replacing a policy also answers to the one in place, a weaker signer can't lift it
+        owner_require_batch_auth(
+            env.clone(),
+            Symbol::new(&env, "set_auth_policy"),
+            vec![&env, fn_name.clone()],
+            (fn_name.clone(), policy.clone()).into_val(&env),
+            tx_signature,
+        )?;
+
+        write_auth_policy(&env, fn_name, policy)
+    }
+
+    ///Remove Function Auth Policy
+    fn remove_auth_policy(
+        env: Env,
+        fn_name: Symbol,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError> {
+        owner_require_batch_auth(
+            env.clone(),
+            Symbol::new(&env, "remove_auth_policy"),
+            vec![&env, fn_name.clone()],
+            (fn_name.clone(),).into_val(&env),
+            tx_signature,
+        )?;
+
+        write_remove_auth_policy(&env, fn_name)
+    }
+
+    ///Get Function Auth Policies
+    fn get_auth_policies(env: Env) -> Map<Symbol, AuthPolicy> {
+        read_auth_policies(&env)
+    }
+
+    ///Set Dual Control Mode
+    fn set_dual_control(
+        env: Env,
+        enabled: bool,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError> {
+        owner_require_auth(
+            env.clone(),
+            Symbol::new(&env, "set_dual_control"),
+            (enabled,).into_val(&env),
+            tx_signature,
+        )?;
+
+        write_dual_control(&env, enabled)
+    }
+
+    ///Get Dual Control Mode
+    fn get_dual_control(env: Env) -> bool {
+        read_dual_control(&env)
+    }
+
+    ///Execute Pending Admin Change
+    fn execute_admin_change(env: Env, id: u32) -> Result<(), ContractError> {
+        execute_pending_admin_change(&env, id)
+    }
+
+    ///Cancel Pending Admin Change
+    fn cancel_admin_change(
+        env: Env,
+        id: u32,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError> {
+        owner_require_auth(
+            env.clone(),
+            Symbol::new(&env, "cancel_admin_change"),
+            (id,).into_val(&env),
+            tx_signature,
+        )?;
+
+        cancel_pending_admin_change(&env, id)
+    }
+
+    ///Set Admin Change Delay
+    fn set_admin_change_delay(
+        env: Env,
+        delay_ledgers: u32,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError> {
+        owner_require_auth(
+            env.clone(),
+            Symbol::new(&env, "set_admin_change_delay"),
+            (delay_ledgers,).into_val(&env),
+            tx_signature,
+        )?;
+
+        queue_admin_change(&env, AdminChange::Delay(delay_ledgers))
+    }
+
+    ///Get Admin Change Delay
+    fn get_admin_change_delay(env: Env) -> u32 {
+        read_admin_change_delay(&env)
+    }
+
+    ///Get Pending Admin Changes
+    fn get_pending_changes(env: Env) -> Vec<PendingAdminChange> {
+        read_pending_changes(&env)
+    }
+
+    ///Freeze Wallet Outflows
+    fn freeze(env: Env, tx_signature: Option<TxSignature>) -> Result<(), ContractError> {
+        owner_require_auth(
+            env.clone(),
+            Symbol::new(&env, "freeze"),
+            Vec::new(&env),
+            tx_signature,
+        )?;
+
+        write_freeze(&env);
+        Ok(())
+    }
+
+    ///Freeze Wallet Outflows As Guardian
+    fn guardian_freeze(
+        env: Env,
+        guardian_index: u32,
+        proof: GuardianProof,
+    ) -> Result<(), ContractError> {
+        freeze::guardian_freeze(&env, guardian_index, proof)
+    }
+
+    ///Request Wallet Unfreeze
+    fn request_unfreeze(env: Env, tx_signature: Option<TxSignature>) -> Result<(), ContractError> {
+        let auth = owner_require_auth(
+            env.clone(),
+            Symbol::new(&env, "request_unfreeze"),
+            Vec::new(&env),
+            tx_signature,
+        )?;
+
+        freeze::request_unfreeze(&env, &auth)
+    }
+
+    ///Unfreeze Wallet
+    fn unfreeze(env: Env) -> Result<(), ContractError> {
+        execute_unfreeze(&env)
+    }
+
+    ///Get Freeze State
+    fn get_freeze_state(env: Env) -> FreezeState {
+        read_freeze_state(&env)
+    }
+
+    ///Get BLS Signer Pubkeys
+    fn get_bls_signers(env: Env) -> Vec<BytesN<96>> {
+        read_bls_pubkeys(&env)
+    }
+
+    ///Get BLS Signing Threshold
+    fn get_bls_threshold(env: Env) -> u32 {
+        read_bls_threshold(&env)
+    }
+
+    ///Get Token Spending Limit
+    fn get_token_limit(env: Env, token_id: Address) -> Option<TokenLimit> {
+        read_token_limit(&env, &token_id)
     }
 
     ///Get Token Balance
@@ -292,10 +1094,73 @@ impl AccountTrait for Account {
     fn upgrade(
         e: Env,
         new_wasm_hash: BytesN<32>,
-        tx_signature: Option<BytesN<192>>,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError> {
+        require_not_frozen(&e)?;
+
+        owner_require_auth(
+            e.clone(),
+            Symbol::new(&e, "upgrade"),
+            (new_wasm_hash.clone(),).into_val(&e),
+            tx_signature,
+        )?;
+        queue_admin_change(&e, AdminChange::Upgrade(new_wasm_hash))
+    }
+}
+
+#[contractimpl]
+impl CustomAccountInterface for Account {
+    type Signature = Vec<SignerProof>;
+    type Error = ContractError;
+
+    ///Verify Wallet Authorization
+    fn __check_auth(
+        env: Env,
+        signature_payload: Hash<32>,
+        signature: Vec<SignerProof>,
+        auth_contexts: Vec<Context>,
     ) -> Result<(), ContractError> {
-        owner_require_auth(e.clone(), tx_signature)?;
-        e.deployer().update_current_contract_wasm(new_wasm_hash);
+        // Wallet-signed token transfers are outflows too
+        require_not_frozen(&env)?;
+
+        // Every context answers to the entrypoint it stands in for: calls to invoke, token
+        // outflows to withdraw as well, contract creation to a "create_contract" policy the
+        // owner has to set first
+        let mut auth_fns = Vec::new(&env);
+        for context in auth_contexts.iter() {
+            match context {
+                Context::Contract(context) => {
+                    // Calls into the wallet would skip its own entrypoint checks
+                    if context.contract == env.current_contract_address() {
+                        return Err(ContractError::InvalidInvokeTarget);
+                    }
+                    auth_fns.push_back(Symbol::new(&env, "invoke"));
+                    if is_wallet_outflow(&env, &context.fn_name, &context.args) {
+                        auth_fns.push_back(Symbol::new(&env, "withdraw"));
+                    }
+                }
+                Context::CreateContractHostFn(_) | Context::CreateContractWithCtorHostFn(_) => {
+                    let create_contract = Symbol::new(&env, "create_contract");
+                    if !read_auth_policies(&env).contains_key(create_contract.clone()) {
+                        return Err(ContractError::ContractCreationNotAllowed);
+                    }
+                    auth_fns.push_back(create_contract);
+                }
+            }
+        }
+        let auth = check_contract_auth(&env, &signature_payload.to_bytes(), &auth_fns, signature)?;
+
+        for context in auth_contexts.iter() {
+            if let Context::Contract(context) = context {
+                enforce_invocation_limits(
+                    &env,
+                    &auth,
+                    &context.contract,
+                    &context.fn_name,
+                    &context.args,
+                )?;
+            }
+        }
         Ok(())
     }
 }
@@ -1,23 +1,26 @@
 use soroban_sdk::{token, Address, Env, Vec};
 
-use crate::data::DataKey;
+use crate::{data::DataKey, token_list::write_token_activity};
 
 pub fn take_token(env: &Env, from: &Address, token_id: &Address, amount: i128) {
     let token = token::Client::new(env, token_id);
     let contract_address = env.current_contract_address();
     token.transfer(from, &contract_address, &amount);
+    write_token_activity(env, token_id);
 }
 
 pub fn send_token(env: &Env, to: &Address, token_id: &Address, amount: i128) {
     let token = token::Client::new(env, token_id);
     let contract_address = env.current_contract_address();
     token.transfer(&contract_address, to, &amount);
+    write_token_activity(env, token_id);
 }
 
 pub fn spend_token(env: &Env, spender: &Address, token_id: &Address, amount: i128, to: &Address) {
     let token = token::Client::new(env, token_id);
     let contract_address = env.current_contract_address();
     token.transfer_from(&spender, &contract_address, to, &amount);
+    write_token_activity(env, token_id);
 }
 
 pub fn read_balance(env: &Env, token_id: &Address) -> i128 {
@@ -0,0 +1,127 @@
+use soroban_sdk::{symbol_short, Address, Env, Map, String, Symbol};
+
+use crate::{data::DataKey, error::ContractError, types::AddressBookEntry};
+
+// Roughly a day of ledgers before a newly trusted address can receive funds in allowlist mode
+pub const ADDRESS_COOLING_PERIOD: u32 = 17280;
+
+pub fn read_address_book(env: &Env) -> Map<Address, AddressBookEntry> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::AddressBook)
+        .unwrap_or(Map::new(env))
+}
+
+fn write_address_book(env: &Env, address_book: &Map<Address, AddressBookEntry>) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::AddressBook, address_book);
+}
+
+// Adds or relabels an entry. Newly granted trust restarts the cooling-off period,
+// so a leaked key can't add a destination and drain to it straight away.
+pub fn write_address_book_entry(env: &Env, address: Address, label: String, trusted: bool) {
+    let mut address_book = read_address_book(env);
+    let sequence = env.ledger().sequence();
+
+    let entry = match address_book.get(address.clone()) {
+        Some(entry) => AddressBookEntry {
+            label,
+            trusted,
+            usable_after: if trusted && !entry.trusted {
+                sequence + ADDRESS_COOLING_PERIOD
+            } else {
+                entry.usable_after
+            },
+            ..entry
+        },
+        None => AddressBookEntry {
+            address: address.clone(),
+            label,
+            trusted,
+            added_ledger: sequence,
+            usable_after: sequence + ADDRESS_COOLING_PERIOD,
+        },
+    };
+    address_book.set(address, entry.clone());
+    write_address_book(env, &address_book);
+
+    env.events().publish(
+        (Symbol::new(env, "address_book"), symbol_short!("set")),
+        entry,
+    );
+}
+
+pub fn write_remove_address_book_entry(env: &Env, address: Address) -> Result<(), ContractError> {
+    let mut address_book = read_address_book(env);
+    if address_book.remove(address.clone()).is_none() {
+        return Err(ContractError::AddressNotListed);
+    }
+    write_address_book(env, &address_book);
+
+    env.events().publish(
+        (Symbol::new(env, "address_book"), symbol_short!("removed")),
+        address,
+    );
+    Ok(())
+}
+
+// Stays on until a requested switch-off has waited out the cooling-off period
+pub fn read_allowlist_mode(env: &Env) -> bool {
+    let enabled = env
+        .storage()
+        .persistent()
+        .get(&DataKey::AllowlistMode)
+        .unwrap_or(false);
+    match env
+        .storage()
+        .persistent()
+        .get::<DataKey, u32>(&DataKey::AllowlistOffAfter)
+    {
+        Some(off_after) => enabled && env.ledger().sequence() < off_after,
+        None => enabled,
+    }
+}
+
+// Switching on is immediate and drops a pending switch-off. Switching off waits as long as
+// a new address would, otherwise a leaked key could lift the list and drain right away.
+pub fn write_allowlist_mode(env: &Env, enabled: bool) {
+    if enabled {
+        env.storage()
+            .persistent()
+            .set(&DataKey::AllowlistMode, &true);
+        env.storage()
+            .persistent()
+            .remove(&DataKey::AllowlistOffAfter);
+    } else if read_allowlist_mode(env)
+        && !env.storage().persistent().has(&DataKey::AllowlistOffAfter)
+    {
+        env.storage().persistent().set(
+            &DataKey::AllowlistOffAfter,
+            &(env.ledger().sequence() + ADDRESS_COOLING_PERIOD),
+        );
+    }
+
+    env.events().publish(
+        (Symbol::new(env, "address_book"), symbol_short!("allowlist")),
+        enabled,
+    );
+}
+
+// In allowlist mode funds only leave to trusted entries past their cooling-off period
+pub fn require_allowed_destination(env: &Env, to: &Address) -> Result<(), ContractError> {
+    if !read_allowlist_mode(env) {
+        return Ok(());
+    }
+
+    let entry = read_address_book(env)
+        .get(to.clone())
+        .ok_or(ContractError::DestinationNotAllowed)?;
+    if !entry.trusted {
+        return Err(ContractError::DestinationNotAllowed);
+    }
+    if env.ledger().sequence() < entry.usable_after {
+        return Err(ContractError::DestinationCoolingOff);
+    }
+    Ok(())
+}
@@ -0,0 +1,137 @@
+use soroban_sdk::{symbol_short, Env, Map, Symbol, Vec};
+
+use crate::{
+    access::{write_dapp_router_contract_id, write_master_contract_id, write_user_account},
+    data::DataKey,
+    error::ContractError,
+    freeze::require_not_frozen,
+    types::{AdminChange, PendingAdminChange},
+};
+
+// Roughly a day of ledgers until the owner picks their own delay
+pub const DEFAULT_ADMIN_CHANGE_DELAY: u32 = 17280;
+
+pub fn read_admin_change_delay(env: &Env) -> u32 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::AdminChangeDelay)
+        .unwrap_or(DEFAULT_ADMIN_CHANGE_DELAY)
+}
+
+fn read_pending_admin_changes(env: &Env) -> Map<u32, PendingAdminChange> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::PendingAdminChanges)
+        .unwrap_or(Map::new(env))
+}
+
+fn write_pending_admin_changes(env: &Env, pending: &Map<u32, PendingAdminChange>) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::PendingAdminChanges, pending);
+}
+
+pub fn read_pending_changes(env: &Env) -> Vec<PendingAdminChange> {
+    read_pending_admin_changes(env).values()
+}
+
+fn next_admin_change_id(env: &Env) -> u32 {
+    let id: u32 = env
+        .storage()
+        .persistent()
+        .get(&DataKey::AdminChangeId)
+        .unwrap_or(0);
+    env.storage()
+        .persistent()
+        .set(&DataKey::AdminChangeId, &(id + 1));
+    id
+}
+
+fn publish_admin_change_event(env: &Env, action: Symbol, pending: PendingAdminChange) {
+    env.events()
+        .publish((Symbol::new(env, "admin_change"), action), pending);
+}
+
+// Applies the change right away when no delay is configured, raising the delay
+// is never held back since it only makes the wallet stricter
+pub fn queue_admin_change(env: &Env, change: AdminChange) -> Result<(), ContractError> {
+    let delay = read_admin_change_delay(env);
+    let immediate = match &change {
+        AdminChange::Delay(delay_ledgers) => *delay_ledgers >= delay,
+        _ => delay == 0,
+    };
+    if immediate {
+        apply_admin_change(env, change);
+        return Ok(());
+    }
+
+    let pending = PendingAdminChange {
+        id: next_admin_change_id(env),
+        change,
+        execute_after: env.ledger().sequence() + delay,
+    };
+    let mut pending_changes = read_pending_admin_changes(env);
+    pending_changes.set(pending.id, pending.clone());
+    write_pending_admin_changes(env, &pending_changes);
+
+    publish_admin_change_event(env, symbol_short!("queued"), pending);
+    Ok(())
+}
+
+pub fn execute_pending_admin_change(env: &Env, id: u32) -> Result<(), ContractError> {
+    let mut pending_changes = read_pending_admin_changes(env);
+    let pending = pending_changes
+        .get(id)
+        .ok_or(ContractError::AdminChangeNotFound)?;
+    if env.ledger().sequence() < pending.execute_after {
+        return Err(ContractError::AdminChangeNotReady);
+    }
+    if let AdminChange::Upgrade(_) = pending.change {
+        require_not_frozen(env)?;
+    }
+
+    pending_changes.remove(id);
+    write_pending_admin_changes(env, &pending_changes);
+
+    publish_admin_change_event(env, symbol_short!("executed"), pending.clone());
+    apply_admin_change(env, pending.change);
+    Ok(())
+}
+
+pub fn remove_pending_admin_changes(env: &Env) {
+    env.storage()
+        .persistent()
+        .remove(&DataKey::PendingAdminChanges);
+}
+
+pub fn cancel_pending_admin_change(env: &Env, id: u32) -> Result<(), ContractError> {
+    let mut pending_changes = read_pending_admin_changes(env);
+    let pending = pending_changes
+        .get(id)
+        .ok_or(ContractError::AdminChangeNotFound)?;
+    pending_changes.remove(id);
+    write_pending_admin_changes(env, &pending_changes);
+
+    publish_admin_change_event(env, symbol_short!("cancelled"), pending);
+    Ok(())
+}
+
+fn apply_admin_change(env: &Env, change: AdminChange) {
+    match change {
+        AdminChange::MasterContract(master_contract_id) => {
+            write_master_contract_id(env, &master_contract_id)
+        }
+        AdminChange::DappRouter(dapp_router_contract_id) => {
+            write_dapp_router_contract_id(env, &dapp_router_contract_id)
+        }
+        AdminChange::UserAccount(user_account_id) => write_user_account(env, &user_account_id),
+        AdminChange::Delay(delay_ledgers) => env
+            .storage()
+            .persistent()
+            .set(&DataKey::AdminChangeDelay, &delay_ledgers),
+        // Takes effect once the current invocation completes
+        AdminChange::Upgrade(new_wasm_hash) => {
+            env.deployer().update_current_contract_wasm(new_wasm_hash)
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use soroban_sdk::{symbol_short, Env, Map, Symbol, Vec};
+
+use crate::{
+    data::DataKey,
+    error::ContractError,
+    types::{AuthPolicy, PolicyGroup, SignerKind},
+};
+
+pub fn read_auth_policies(env: &Env) -> Map<Symbol, AuthPolicy> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::AuthPolicies)
+        .unwrap_or(Map::new(env))
+}
+
+// Every policy an invocation answers to, a batch answers to those of all its ops
+pub fn read_auth_policies_for(env: &Env, fn_names: &Vec<Symbol>) -> Vec<AuthPolicy> {
+    let policies = read_auth_policies(env);
+    let mut applicable = Vec::new(env);
+    for fn_name in fn_names.iter() {
+        if let Some(policy) = policies.get(fn_name) {
+            applicable.push_back(policy);
+        }
+    }
+    applicable
+}
+
+pub fn write_auth_policy(
+    env: &Env,
+    fn_name: Symbol,
+    policy: AuthPolicy,
+) -> Result<(), ContractError> {
+    if policy.threshold == 0 || policy.threshold > policy.groups.len() {
+        return Err(ContractError::InvalidAuthPolicy);
+    }
+    for group in policy.groups.iter() {
+        if group.threshold == 0 || group.threshold > group.signers.len() {
+            return Err(ContractError::InvalidAuthPolicy);
+        }
+        // A kind listed twice would count twice towards the group threshold
+        for i in 0..group.signers.len() {
+            let kind = group.signers.get_unchecked(i);
+            if group.signers.last_index_of(kind) != Some(i) {
+                return Err(ContractError::InvalidAuthPolicy);
+            }
+        }
+    }
+
+    let mut policies = read_auth_policies(env);
+    policies.set(fn_name.clone(), policy.clone());
+    env.storage()
+        .persistent()
+        .set(&DataKey::AuthPolicies, &policies);
+
+    env.events().publish(
+        (Symbol::new(env, "auth_policy"), symbol_short!("set")),
+        (fn_name, policy),
+    );
+    Ok(())
+}
+
+pub fn write_remove_auth_policy(env: &Env, fn_name: Symbol) -> Result<(), ContractError> {
+    let mut policies = read_auth_policies(env);
+    if policies.remove(fn_name.clone()).is_none() {
+        return Err(ContractError::AuthPolicyNotFound);
+    }
+    env.storage()
+        .persistent()
+        .set(&DataKey::AuthPolicies, &policies);
+
+    env.events().publish(
+        (Symbol::new(env, "auth_policy"), symbol_short!("removed")),
+        fn_name,
+    );
+    Ok(())
+}
+
+fn is_group_met(group: &PolicyGroup, satisfied: &Vec<SignerKind>) -> bool {
+    let met = group
+        .signers
+        .iter()
+        .filter(|kind| satisfied.contains(kind))
+        .count() as u32;
+    met >= group.threshold
+}
+
+// Counts each policy's groups met by the satisfied signer kinds against its threshold
+pub fn check_auth_policies(
+    policies: &Vec<AuthPolicy>,
+    satisfied: &Vec<SignerKind>,
+) -> Result<(), ContractError> {
+    for policy in policies.iter() {
+        let met = policy
+            .groups
+            .iter()
+            .filter(|group| is_group_met(group, satisfied))
+            .count() as u32;
+        if met < policy.threshold {
+            return Err(ContractError::AuthPolicyNotMet);
+        }
+    }
+    Ok(())
+}
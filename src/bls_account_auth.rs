@@ -1,80 +1,558 @@
 use soroban_sdk::{
     bytesn,
     crypto::bls12_381::{G1Affine, G2Affine},
-    vec, Bytes, BytesN, Env,
+    vec,
+    xdr::ToXdr,
+    Bytes, BytesN, Env, Symbol, Val, Vec,
 };
 
 use crate::{
-    access::{read_aggregated_pk_bytes, read_user_account},
+    access::{read_aggregated_pk_bytes, read_bls_pubkeys, read_bls_threshold, read_user_account},
+    auth_policy::{check_auth_policies, read_auth_policies_for},
     data::DataKey,
+    dual_control::enforce_dual_control,
+    ed25519_signers::verify_ed25519_signature,
     error::ContractError,
+    evm_signers::verify_secp256k1_signature,
+    guardians::{read_guardian_config, verify_guardian_proof},
+    session_keys::verify_session_signature,
+    types::{
+        AuthPolicy, BlsSignature, LegacyDst, SignerKind, SignerProof, TxSignature, ValidityKind,
+        ValidityWindow,
+    },
+    webauthn_account_auth::verify_webauthn_signature,
 };
 
+// Separate tag for proof-of-possession, so a PoP can never double as an auth signature
+const POP_DST: &str = "BLS_POP_XMD:SHA-256_SSWU_SOCKETFI";
+
+const DST_PREFIX: &str = "BLS_AUTH_XMD:SHA-256_SSWU_SOCKETFI";
+// Version 1 is the bare prefix, later versions are bound to the network.
+// Bump together with a contract upgrade, existing wallets move over through migrate_dst.
+pub const DST_VERSION: u32 = 2;
+
+// DST_PREFIX || "_V" || version || "_" || hex(network id)
+pub fn build_dst_bytes(env: &Env, version: u32) -> Bytes {
+    let mut dst = Bytes::from_slice(env, DST_PREFIX.as_bytes());
+    if version < 2 {
+        return dst;
+    }
+
+    dst.append(&Bytes::from_slice(env, b"_V"));
+    let mut digits = [0u8; 10];
+    let mut remaining = version;
+    let mut start = digits.len();
+    while remaining > 0 {
+        start -= 1;
+        digits[start] = b'0' + (remaining % 10) as u8;
+        remaining /= 10;
+    }
+    dst.append(&Bytes::from_slice(env, &digits[start..]));
+
+    dst.push_back(b'_');
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+    for byte in env.ledger().network_id().to_array() {
+        dst.push_back(HEX[(byte >> 4) as usize]);
+        dst.push_back(HEX[(byte & 0x0f) as usize]);
+    }
+    dst
+}
+
 pub fn write_dst_bytes(env: &Env) {
-    const DST: &str = "BLS_AUTH_XMD:SHA-256_SSWU_SOCKETFI";
     env.storage()
         .instance()
-        .set(&DataKey::Dst, &Bytes::from_slice(&env, DST.as_bytes()));
+        .set(&DataKey::Dst, &build_dst_bytes(env, DST_VERSION));
+    env.storage()
+        .instance()
+        .set(&DataKey::DstVersion, &DST_VERSION);
 }
 
 pub fn read_dst_bytes(env: &Env) -> Option<Bytes> {
     env.storage().instance().get(&DataKey::Dst).unwrap()
 }
 
-pub fn read_nonce(env: &Env) -> BytesN<32> {
+// Wallets initialized before versioning carry the bare version 1 DST
+pub fn read_dst_version(env: &Env) -> u32 {
     env.storage()
         .instance()
-        .get::<DataKey, BytesN<32>>(&DataKey::Nonce)
-        .unwrap()
+        .get(&DataKey::DstVersion)
+        .unwrap_or(1)
 }
-pub fn write_nonce(env: &Env) {
-    let mut seed = [0u8; 32];
-    env.prng().fill(&mut seed);
+
+// The previous DST, while its grace period lasts
+pub fn read_legacy_dst_bytes(env: &Env) -> Option<Bytes> {
+    let legacy: LegacyDst = env.storage().instance().get(&DataKey::LegacyDst)?;
+    if env.ledger().sequence() > legacy.expiry_ledger {
+        return None;
+    }
+    Some(legacy.dst)
+}
+
+// Switches to the current DST version, signatures under the old DST keep
+// verifying for `grace_ledgers` so in-flight approvals aren't stranded
+pub fn migrate_dst(env: &Env, grace_ledgers: u32) -> Result<(), ContractError> {
+    let version = read_dst_version(env);
+    if version >= DST_VERSION {
+        return Err(ContractError::DstUpToDate);
+    }
+
+    let legacy = LegacyDst {
+        version,
+        dst: read_dst_bytes(env).unwrap(),
+        expiry_ledger: env.ledger().sequence() + grace_ledgers,
+    };
+    env.storage().instance().set(&DataKey::LegacyDst, &legacy);
+    write_dst_bytes(env);
+
+    env.events().publish(
+        (Symbol::new(env, "dst_migrated"), version),
+        (DST_VERSION, legacy.expiry_ledger),
+    );
+    Ok(())
+}
+
+// Independent nonce channels the owner can sign on, so operations can be prepared in parallel
+pub const NONCE_CHANNELS: u32 = 16;
+// Channel reserved for guardian signatures, outside the range open to the owner
+pub const RECOVERY_NONCE_CHANNEL: u32 = NONCE_CHANNELS;
+// Spent by every guardian freeze, kept apart so a freeze doesn't void pending recovery approvals
+pub const FREEZE_NONCE_CHANNEL: u32 = NONCE_CHANNELS + 1;
+
+pub fn read_nonce(env: &Env, channel: u32) -> u64 {
     env.storage()
         .instance()
-        .set(&DataKey::Nonce, &BytesN::from_array(&env, &seed));
+        .get::<DataKey, u64>(&DataKey::Nonce(channel))
+        .unwrap_or(0)
 }
 
-pub fn check_auth(env: &Env, tx_signature: BytesN<192>) -> Result<(), ContractError> {
-    // The sdk module containing access to the bls12_381 functions
+pub fn increment_nonce(env: &Env, channel: u32) {
+    let nonce = read_nonce(env, channel) + 1;
+    env.storage()
+        .instance()
+        .set(&DataKey::Nonce(channel), &nonce);
+}
+
+// The message signed by the owner for a wallet entrypoint:
+// sha256(channel || nonce || aggregated BLS pubkey || wallet address XDR || network id
+//        || fn_name XDR || args XDR [|| kind || valid_after || valid_until])
+// Integers are big-endian, kind is 0 for ledger sequence and 1 for timestamp bounds.
+// Binding the key set means a rotation voids anything signed before it, even on
+// channels that never advanced.
+pub fn build_auth_payload(
+    env: &Env,
+    channel: u32,
+    validity: Option<&ValidityWindow>,
+    fn_name: &Symbol,
+    args: &Vec<Val>,
+) -> BytesN<32> {
+    let mut payload = Bytes::new(env);
+    payload.append(&Bytes::from_array(env, &channel.to_be_bytes()));
+    payload.append(&Bytes::from_array(
+        env,
+        &read_nonce(env, channel).to_be_bytes(),
+    ));
+    payload.append(&read_aggregated_pk_bytes(env).unwrap().into());
+    payload.append(&env.current_contract_address().to_xdr(env));
+    payload.append(&env.ledger().network_id().into());
+    payload.append(&fn_name.to_xdr(env));
+    payload.append(&args.to_xdr(env));
+    if let Some(validity) = validity {
+        payload.append(&Bytes::from_array(
+            env,
+            &(validity.kind as u32).to_be_bytes(),
+        ));
+        payload.append(&Bytes::from_array(env, &validity.valid_after.to_be_bytes()));
+        payload.append(&Bytes::from_array(env, &validity.valid_until.to_be_bytes()));
+    }
+
+    env.crypto().sha256(&payload).to_bytes()
+}
+
+pub fn check_validity_window(env: &Env, validity: &ValidityWindow) -> Result<(), ContractError> {
+    let now = match validity.kind {
+        ValidityKind::Ledger => env.ledger().sequence() as u64,
+        ValidityKind::Timestamp => env.ledger().timestamp(),
+    };
+    if now < validity.valid_after {
+        return Err(ContractError::SignatureNotYetValid);
+    }
+    if now > validity.valid_until {
+        return Err(ContractError::SignatureExpired);
+    }
+    Ok(())
+}
+
+// This is the negative of g1 (generator point of the G1 group)
+fn neg_g1(env: &Env) -> G1Affine {
+    G1Affine::from_bytes(bytesn!(&env, 0x17f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb114d1d6855d545a8aa7d76c8cf2e21f267816aef1db507c96655b9d5caac42364e6f38ba0ecb751bad54dcd6b939c2ca))
+}
+
+// Rejects keys that are off-curve, outside the prime-order subgroup or the identity point
+pub fn check_bls_pubkey(env: &Env, pubkey: &BytesN<96>) -> Result<G1Affine, ContractError> {
+    let bls = env.crypto().bls12_381();
+
+    // Uncompressed encoding of the point at infinity: infinity flag set, all other bits zero
+    let mut identity = [0u8; 96];
+    identity[0] = 0x40;
+    if pubkey.to_array() == identity {
+        return Err(ContractError::InvalidBlsPublicKey);
+    }
+
+    let pk = G1Affine::from_bytes(pubkey.clone());
+    if !bls.g1_is_in_subgroup(&pk) {
+        return Err(ContractError::InvalidBlsPublicKey);
+    }
+    Ok(pk)
+}
+
+// A PoP is the key holder's signature over their own serialized public key under POP_DST
+pub fn verify_bls_pop(
+    env: &Env,
+    pubkey: &BytesN<96>,
+    pop: BytesN<192>,
+) -> Result<(), ContractError> {
+    let bls = env.crypto().bls12_381();
+
+    let pk = check_bls_pubkey(env, pubkey)?;
+    let dst = Bytes::from_slice(env, POP_DST.as_bytes());
+    let msg_g2 = bls.hash_to_g2(&pubkey.clone().into(), &dst);
+
+    let vp1 = vec![env, pk, neg_g1(env)];
+    let vp2 = vec![env, msg_g2, G2Affine::from_bytes(pop)];
+
+    if !bls.pairing_check(vp1, vp2) {
+        return Err(ContractError::InvalidProofOfPossession);
+    }
+    Ok(())
+}
+
+// Sums the pubkeys of the signers flagged in the bitmap (bit i = i-th registered key)
+pub fn aggregate_signer_pubkeys(env: &Env, signers: u32) -> Result<G1Affine, ContractError> {
     let bls = env.crypto().bls12_381();
 
-    // Retrieve the aggregated pubkey and the DST from storage
-    let agg_pk: BytesN<96> = read_aggregated_pk_bytes(&env).unwrap();
+    let pubkeys = read_bls_pubkeys(env);
+    let key_count = pubkeys.len();
+
+    // Bits past the registered keys don't map to any signer
+    if key_count < 32 && signers >> key_count != 0 {
+        return Err(ContractError::InvalidSignerBitmap);
+    }
+    if signers.count_ones() < read_bls_threshold(env) {
+        return Err(ContractError::ThresholdNotMet);
+    }
+
+    // Full key set, the aggregate is already stored
+    if signers.count_ones() == key_count {
+        return Ok(G1Affine::from_bytes(read_aggregated_pk_bytes(env).unwrap()));
+    }
+
+    let mut agg_pk: Option<G1Affine> = None;
+    for i in 0..key_count {
+        if signers & (1 << i) == 0 {
+            continue;
+        }
+        let pk = G1Affine::from_bytes(pubkeys.get_unchecked(i));
+        agg_pk = Some(match agg_pk {
+            Some(agg) => bls.g1_add(&agg, &pk),
+            None => pk,
+        });
+    }
+
+    agg_pk.ok_or(ContractError::ThresholdNotMet)
+}
+
+pub fn verify_bls_signature(
+    env: &Env,
+    msg: &Bytes,
+    tx_signature: BlsSignature,
+) -> Result<(), ContractError> {
+    // Aggregate the participating pubkeys
+    let agg_pk = aggregate_signer_pubkeys(env, tx_signature.signers)?;
+    verify_bls_pairing(env, agg_pk, msg, tx_signature.signature)
+}
+
+pub fn verify_bls_pairing(
+    env: &Env,
+    pubkey: G1Affine,
+    msg: &Bytes,
+    signature: BytesN<192>,
+) -> Result<(), ContractError> {
+    // Retrieve the DST from storage
     let dst: Bytes = read_dst_bytes(&env).unwrap();
+    if bls_pairing_check(env, &pubkey, msg, &signature, &dst) {
+        return Ok(());
+    }
+
+    // Fall back to the previous DST during a migration's grace period
+    if let Some(legacy_dst) = read_legacy_dst_bytes(env) {
+        if bls_pairing_check(env, &pubkey, msg, &signature, &legacy_dst) {
+            return Ok(());
+        }
+    }
+    Err(ContractError::InvalidSignature)
+}
 
-    // This is the negative of g1 (generator point of the G1 group)
+fn bls_pairing_check(
+    env: &Env,
+    pubkey: &G1Affine,
+    msg: &Bytes,
+    signature: &BytesN<192>,
+    dst: &Bytes,
+) -> bool {
+    // The sdk module containing access to the bls12_381 functions
+    let bls = env.crypto().bls12_381();
 
-    let neg_g1 = G1Affine::from_bytes(bytesn!(&env, 0x17f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb114d1d6855d545a8aa7d76c8cf2e21f267816aef1db507c96655b9d5caac42364e6f38ba0ecb751bad54dcd6b939c2ca));
-    // Hash the signature_payload i.e. the msg being signed and to be
-    // verified into a point in G2
-    let nonce = read_nonce(&env);
-    let msg_g2 = bls.hash_to_g2(&nonce.into(), &dst);
+    let neg_g1 = neg_g1(env);
+    // Hash the msg being signed and to be verified into a point in G2
+    let msg_g2 = bls.hash_to_g2(msg, dst);
 
     // Prepare inputs to the pairing function
-    let vp1 = vec![&env, G1Affine::from_bytes(agg_pk), neg_g1];
-    let vp2 = vec![&env, msg_g2, G2Affine::from_bytes(tx_signature)];
+    let vp1 = vec![&env, pubkey.clone(), neg_g1];
+    let vp2 = vec![&env, msg_g2, G2Affine::from_bytes(signature.clone())];
 
     // thus it must equal to the RHS if the signature matches.
-    let is_valid_sig = bls.pairing_check(vp1, vp2);
-    if !is_valid_sig {
-        return Err(ContractError::InvalidSignature);
+    bls.pairing_check(vp1, vp2)
+}
+
+// Who authorized an owner entrypoint
+pub enum AuthMethod {
+    Bls,
+    // BLS owner and external account together, only reachable through an auth policy
+    BlsAndUserAccount,
+    Passkey,
+    Ed25519,
+    Secp256k1,
+    UserAccount,
+    Guardian,
+    Session(BytesN<32>),
+}
+
+// Checks an owner proof of any supported signer type against a 32 byte message
+pub fn verify_signer_proof(
+    env: &Env,
+    payload: &BytesN<32>,
+    tx_signature: SignerProof,
+) -> Result<(), ContractError> {
+    match tx_signature {
+        SignerProof::Bls(signature) => {
+            verify_bls_signature(env, &payload.clone().into(), signature)
+        }
+        SignerProof::WebAuthn(signature) => verify_webauthn_signature(env, payload, signature),
+        SignerProof::Ed25519(signature) => verify_ed25519_signature(env, payload, signature),
+        SignerProof::Secp256k1(signature) => verify_secp256k1_signature(env, payload, signature),
+        // Session keys are scoped to wallet entrypoints and go through owner_require_auth
+        SignerProof::Session(_) => Err(ContractError::SessionNotAllowed),
+        // Only meaningful alongside an auth policy
+        SignerProof::Account | SignerProof::Guardian(_) => {
+            Err(ContractError::UnsupportedSignerProof)
+        }
+    }
+}
+
+// Without a policy an entrypoint takes exactly one owner proof or a session
+fn check_single_proof(
+    env: &Env,
+    payload: &BytesN<32>,
+    fn_name: &Symbol,
+    proofs: Vec<SignerProof>,
+) -> Result<AuthMethod, ContractError> {
+    if proofs.len() != 1 {
+        return Err(ContractError::UnsupportedSignerProof);
+    }
+
+    match proofs.get_unchecked(0) {
+        SignerProof::Session(signature) => {
+            let public_key = signature.public_key.clone();
+            verify_session_signature(env, payload, fn_name, signature)?;
+            Ok(AuthMethod::Session(public_key))
+        }
+        SignerProof::Account => {
+            read_user_account(env)?.require_auth();
+            Ok(AuthMethod::UserAccount)
+        }
+        signature => {
+            let auth = match signature {
+                SignerProof::WebAuthn(_) => AuthMethod::Passkey,
+                SignerProof::Ed25519(_) => AuthMethod::Ed25519,
+                SignerProof::Secp256k1(_) => AuthMethod::Secp256k1,
+                _ => AuthMethod::Bls,
+            };
+            verify_signer_proof(env, payload, signature)?;
+            Ok(auth)
+        }
     }
-    write_nonce(env);
-    Ok(())
+}
+
+// Verifies every proof, then checks the signer kinds they cover against the policy.
+// Sessions are never accepted here, a policy names its signers explicitly.
+fn check_policy_proofs(
+    env: &Env,
+    policies: &Vec<AuthPolicy>,
+    payload: &BytesN<32>,
+    proofs: Vec<SignerProof>,
+) -> Result<AuthMethod, ContractError> {
+    let mut satisfied: Vec<SignerKind> = Vec::new(env);
+    let mut guardian_approvals: Vec<u32> = Vec::new(env);
+
+    for proof in proofs.iter() {
+        let kind = match proof {
+            SignerProof::Bls(signature) => {
+                verify_bls_signature(env, &payload.clone().into(), signature)?;
+                SignerKind::Bls
+            }
+            SignerProof::WebAuthn(signature) => {
+                verify_webauthn_signature(env, payload, signature)?;
+                SignerKind::Passkey
+            }
+            SignerProof::Ed25519(signature) => {
+                verify_ed25519_signature(env, payload, signature)?;
+                SignerKind::Ed25519
+            }
+            SignerProof::Secp256k1(signature) => {
+                verify_secp256k1_signature(env, payload, signature)?;
+                SignerKind::Secp256k1
+            }
+            SignerProof::Session(_) => return Err(ContractError::SessionNotAllowed),
+            SignerProof::Account => {
+                if satisfied.contains(SignerKind::Account) {
+                    return Err(ContractError::UnsupportedSignerProof);
+                }
+                read_user_account(env)?.require_auth();
+                SignerKind::Account
+            }
+            SignerProof::Guardian(signature) => {
+                let config = read_guardian_config(env).ok_or(ContractError::GuardiansNotSet)?;
+                if guardian_approvals.contains(signature.index) {
+                    return Err(ContractError::GuardianAlreadyApproved);
+                }
+                let guardian = config
+                    .guardians
+                    .get(signature.index)
+                    .ok_or(ContractError::GuardianNotFound)?;
+                verify_guardian_proof(env, guardian, signature.proof, payload)?;
+
+                guardian_approvals.push_back(signature.index);
+                if guardian_approvals.len() < config.threshold {
+                    continue;
+                }
+                SignerKind::Guardian
+            }
+        };
+        if !satisfied.contains(kind) {
+            satisfied.push_back(kind);
+        }
+    }
+    check_auth_policies(policies, &satisfied)?;
+
+    let bls = satisfied.contains(SignerKind::Bls);
+    let account = satisfied.contains(SignerKind::Account);
+    Ok(if bls && account {
+        AuthMethod::BlsAndUserAccount
+    } else if bls {
+        AuthMethod::Bls
+    } else if account {
+        AuthMethod::UserAccount
+    } else if satisfied.contains(SignerKind::Passkey) {
+        AuthMethod::Passkey
+    } else if satisfied.contains(SignerKind::Ed25519) {
+        AuthMethod::Ed25519
+    } else if satisfied.contains(SignerKind::Secp256k1) {
+        AuthMethod::Secp256k1
+    } else {
+        AuthMethod::Guardian
+    })
+}
+
+// Host-driven authorization of the wallet in __check_auth. The proofs answer to the policies
+// and dual control setting of `auth_fns` like an entrypoint would. Replay protection comes
+// from the host, so no nonce is spent, and sessions stay limited to wallet entrypoints.
+pub fn check_contract_auth(
+    env: &Env,
+    payload: &BytesN<32>,
+    auth_fns: &Vec<Symbol>,
+    proofs: Vec<SignerProof>,
+) -> Result<AuthMethod, ContractError> {
+    if proofs
+        .iter()
+        .any(|proof| matches!(proof, SignerProof::Session(_)))
+    {
+        return Err(ContractError::SessionNotAllowed);
+    }
+
+    let policies = read_auth_policies_for(env, auth_fns);
+    let auth = if policies.is_empty() {
+        check_single_proof(env, payload, &Symbol::new(env, "__check_auth"), proofs)?
+    } else {
+        check_policy_proofs(env, &policies, payload, proofs)?
+    };
+    enforce_dual_control(env, auth_fns, auth)
+}
+
+fn check_auth_with_policies(
+    env: &Env,
+    fn_name: &Symbol,
+    args: &Vec<Val>,
+    tx_signature: TxSignature,
+    policies: &Vec<AuthPolicy>,
+) -> Result<AuthMethod, ContractError> {
+    let channel = tx_signature.channel;
+    if channel >= NONCE_CHANNELS {
+        return Err(ContractError::InvalidNonceChannel);
+    }
+
+    check_validity_window(env, &tx_signature.validity)?;
+
+    let payload = build_auth_payload(env, channel, Some(&tx_signature.validity), fn_name, args);
+    let auth = if policies.is_empty() {
+        check_single_proof(env, &payload, fn_name, tx_signature.proofs)?
+    } else {
+        check_policy_proofs(env, policies, &payload, tx_signature.proofs)?
+    };
+    increment_nonce(env, channel);
+    Ok(auth)
 }
 
 pub fn owner_require_auth(
     env: Env,
-    tx_signature: Option<BytesN<192>>,
-) -> Result<(), ContractError> {
-    if let Some(signature) = tx_signature {
-        check_auth(&env, signature)?;
+    fn_name: Symbol,
+    args: Vec<Val>,
+    tx_signature: Option<TxSignature>,
+) -> Result<AuthMethod, ContractError> {
+    let auth_fns = vec![&env, fn_name.clone()];
+    require_owner_auth_for(&env, &fn_name, &auth_fns, &args, tx_signature)
+}
+
+// One owner signature over `fn_name` covering several operations, it has to meet
+// the auth policy and dual control requirements of every one of them
+pub fn owner_require_batch_auth(
+    env: Env,
+    fn_name: Symbol,
+    op_fns: Vec<Symbol>,
+    args: Vec<Val>,
+    tx_signature: Option<TxSignature>,
+) -> Result<AuthMethod, ContractError> {
+    let mut auth_fns = vec![&env, fn_name.clone()];
+    auth_fns.append(&op_fns);
+    require_owner_auth_for(&env, &fn_name, &auth_fns, &args, tx_signature)
+}
+
+fn require_owner_auth_for(
+    env: &Env,
+    fn_name: &Symbol,
+    auth_fns: &Vec<Symbol>,
+    args: &Vec<Val>,
+    tx_signature: Option<TxSignature>,
+) -> Result<AuthMethod, ContractError> {
+    let policies = read_auth_policies_for(env, auth_fns);
+    let auth = if let Some(signature) = tx_signature {
+        check_auth_with_policies(env, fn_name, args, signature, &policies)?
     } else {
-        let user_account = read_user_account(&env)?;
-        user_account.require_auth();
-        write_nonce(&env)
-    }
+        check_auth_policies(&policies, &vec![env, SignerKind::Account])?;
 
-    Ok(())
+        // Soroban tracks its own nonces for the external account, ours stay untouched
+        let user_account = read_user_account(env)?;
+        user_account.require_auth();
+        AuthMethod::UserAccount
+    };
+    enforce_dual_control(env, auth_fns, auth)
 }
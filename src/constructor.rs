@@ -1,30 +1,40 @@
-use soroban_sdk::{contract, Address, BytesN, Env, String, Vec};
+use soroban_sdk::{contract, Address, Bytes, BytesN, Env, String, Vec};
 
 use crate::{
     access::{
-        write_aggregated_pk_bytes, write_dapp_router_contract_id, write_master_contract_id,
-        write_web_keys_bytes,
+        write_add_web_key, write_aggregated_pk_bytes, write_bls_threshold,
+        write_dapp_router_contract_id, write_master_contract_id, write_social_profile,
     },
     account_token::write_allowance_expiration,
-    bls_account_auth::{write_dst_bytes, write_nonce},
+    bls_account_auth::write_dst_bytes,
     error::ContractError,
 };
 
 pub fn init_constructor(
     env: Env,
     bls_pubkeys: Vec<BytesN<96>>,
+    bls_pops: Vec<BytesN<192>>,
+    bls_threshold: u32,
     platform: String,
     social_username: String,
     web_pubkey: BytesN<77>,
+    web_credential_id: Bytes,
     master_contract_id: Address,
     dapp_router_contract_id: Address,
 ) -> Result<(), ContractError> {
-    write_aggregated_pk_bytes(&env, bls_pubkeys);
-    write_web_keys_bytes(&env, platform, social_username, web_pubkey);
+    write_aggregated_pk_bytes(&env, bls_pubkeys, bls_pops)?;
+    write_bls_threshold(&env, bls_threshold)?;
+    write_social_profile(&env, social_username);
+    write_add_web_key(
+        &env,
+        web_credential_id,
+        web_pubkey,
+        String::from_str(&env, "primary"),
+        platform,
+    )?;
     write_dst_bytes(&env);
     write_master_contract_id(&env, &master_contract_id);
     write_dapp_router_contract_id(&env, &dapp_router_contract_id);
     write_allowance_expiration(&env, 17000);
-    write_nonce(&env);
     Ok(())
 }
@@ -7,15 +7,43 @@ pub enum DataKey {
     MasterContractId,
     DappRouterId,
     BlsPublicKey,
+    BlsSigners,
+    BlsThreshold,
+    KeyRotationDelay,
+    PendingKeyRotation,
+    PendingKeyRotationDelay,
+    SessionKeys,
+    AuthPolicies,
+    DualControl,
+    AdminChangeDelay,
+    AdminChangeId,
+    PendingAdminChanges,
+    Freeze,
+    Ed25519Signers,
+    EvmSigners,
+    AddressBook,
+    AllowlistMode,
+    AllowlistOffAfter,
+    TokenLimit(Address),
+    TokenSpendWindow(Address),
+    Guardians,
+    Recovery,
+    // Single passkey of wallets deployed before the registry, moved by migrate_web_key
     WebKey,
+    WebKeys,
     Allowance(Address, Address),
     PrimarySocialProfile,
     AllowanceExpiration,
     SmartAllowance(Address),
     Balance(Address),
     TokenList,
-    Nonce,
+    TokenMetadata(Address),
+    TokenActivity(Address),
+    HiddenTokens,
+    Nonce(u32),
     Dst,
+    DstVersion,
+    LegacyDst,
     TransactionNonce,
     SoroswapContract,
     PairContract,
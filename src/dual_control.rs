@@ -0,0 +1,94 @@
+use soroban_sdk::{symbol_short, Env, Symbol, Vec};
+
+use crate::{
+    access::read_user_account, bls_account_auth::AuthMethod, data::DataKey, error::ContractError,
+};
+
+// Entrypoints that need the BLS owner and the external account together while dual control is on.
+// set_dual_control is listed so switching the mode off takes both as well. freeze and the
+// cancel_* timelock entrypoints stay open to either credential, they only stop a change in
+// flight; cancel_recovery is the exception since a recovery is what replaces a leaked key set.
+const DUAL_CONTROL_FNS: [&str; 27] = [
+    "withdraw",
+    "approve",
+    "invoke",
+    "upgrade",
+    "set_user_account",
+    "update_master_contract",
+    "update_dapp_router_contract",
+    "rotate_bls_keys",
+    "set_guardians",
+    "set_auth_policy",
+    "remove_auth_policy",
+    "set_token_limit",
+    "remove_token_limit",
+    "add_web_key",
+    "migrate_web_key",
+    "add_ed25519_signer",
+    "add_evm_signer",
+    "set_address_book_entry",
+    "set_allowlist_mode",
+    "add_session_key",
+    "set_key_rotation_delay",
+    "set_admin_change_delay",
+    "set_dual_control",
+    "request_unfreeze",
+    "update_allowance_expiration",
+    "migrate_dst",
+    "cancel_recovery",
+];
+
+pub fn read_dual_control(env: &Env) -> bool {
+    env.storage()
+        .persistent()
+        .get(&DataKey::DualControl)
+        .unwrap_or(false)
+}
+
+pub fn write_dual_control(env: &Env, enabled: bool) -> Result<(), ContractError> {
+    // The mode is meaningless without a linked account to co-sign
+    if enabled {
+        read_user_account(env)?;
+    }
+    env.storage()
+        .persistent()
+        .set(&DataKey::DualControl, &enabled);
+
+    env.events().publish(
+        (Symbol::new(env, "dual_control"), symbol_short!("set")),
+        enabled,
+    );
+    Ok(())
+}
+
+fn is_dual_control_fn(env: &Env, fn_name: &Symbol) -> bool {
+    DUAL_CONTROL_FNS
+        .iter()
+        .any(|name| Symbol::new(env, name) == *fn_name)
+}
+
+// Upgrades a BLS authorization to BLS plus the external account when the mode requires it.
+// Sessions are refused too, one registered before the mode was enabled would otherwise
+// keep a single key in control.
+pub fn enforce_dual_control(
+    env: &Env,
+    fn_names: &Vec<Symbol>,
+    auth: AuthMethod,
+) -> Result<AuthMethod, ContractError> {
+    if !read_dual_control(env)
+        || !fn_names
+            .iter()
+            .any(|fn_name| is_dual_control_fn(env, &fn_name))
+    {
+        return Ok(auth);
+    }
+
+    match auth {
+        AuthMethod::Bls => {
+            read_user_account(env)?.require_auth();
+            Ok(AuthMethod::BlsAndUserAccount)
+        }
+        AuthMethod::BlsAndUserAccount => Ok(auth),
+        _ => Err(ContractError::DualControlRequired),
+    }
+}
@@ -0,0 +1,81 @@
+use soroban_sdk::{symbol_short, BytesN, Env, Map, String, Symbol};
+
+use crate::{
+    data::DataKey,
+    error::ContractError,
+    types::{Ed25519Signature, Ed25519Signer},
+};
+
+const MAX_ED25519_SIGNERS: u32 = 10;
+
+pub fn read_ed25519_signers(env: &Env) -> Map<BytesN<32>, Ed25519Signer> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Ed25519Signers)
+        .unwrap_or(Map::new(env))
+}
+
+pub fn write_add_ed25519_signer(
+    env: &Env,
+    public_key: BytesN<32>,
+    label: String,
+) -> Result<(), ContractError> {
+    let mut signers = read_ed25519_signers(env);
+    if signers.contains_key(public_key.clone()) {
+        return Err(ContractError::Ed25519SignerAlreadyExists);
+    }
+    if signers.len() >= MAX_ED25519_SIGNERS {
+        return Err(ContractError::TooManyEd25519Signers);
+    }
+
+    let signer = Ed25519Signer {
+        public_key: public_key.clone(),
+        label,
+        created_ledger: env.ledger().sequence(),
+    };
+    signers.set(public_key.clone(), signer);
+    env.storage()
+        .persistent()
+        .set(&DataKey::Ed25519Signers, &signers);
+
+    env.events().publish(
+        (Symbol::new(env, "ed25519_signer"), symbol_short!("added")),
+        public_key,
+    );
+    Ok(())
+}
+
+pub fn write_remove_ed25519_signer(env: &Env, public_key: BytesN<32>) -> Result<(), ContractError> {
+    let mut signers = read_ed25519_signers(env);
+    if signers.remove(public_key.clone()).is_none() {
+        return Err(ContractError::Ed25519SignerNotFound);
+    }
+    env.storage()
+        .persistent()
+        .set(&DataKey::Ed25519Signers, &signers);
+
+    env.events().publish(
+        (Symbol::new(env, "ed25519_signer"), symbol_short!("removed")),
+        public_key,
+    );
+    Ok(())
+}
+
+// Verifies a registered ed25519 signer over the owner payload
+pub fn verify_ed25519_signature(
+    env: &Env,
+    payload: &BytesN<32>,
+    tx_signature: Ed25519Signature,
+) -> Result<(), ContractError> {
+    if !read_ed25519_signers(env).contains_key(tx_signature.public_key.clone()) {
+        return Err(ContractError::Ed25519SignerNotFound);
+    }
+
+    // Traps the invocation if the signature doesn't match
+    env.crypto().ed25519_verify(
+        &tx_signature.public_key,
+        &payload.clone().into(),
+        &tx_signature.signature,
+    );
+    Ok(())
+}
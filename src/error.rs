@@ -1,3 +1,99 @@
-soroban_sdk::contractimport!(
-    file = "../global_errors/target/wasm32-unknown-unknown/release/socketfi_global_errors.wasm"
-);
+use soroban_sdk::contracterror;
+
+// Wallet errors live in this crate, global_errors doesn't carry the wallet specific
+// variants. The first three are the ones the contract used from global_errors; they are
+// numbered 1-3 here, which needn't match their global_errors codes, so clients decoding
+// raw error codes have to switch to this enum.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum ContractError {
+    AlreadyInitialized = 1,
+    InvalidSignature = 2,
+    UserAccountNotFound = 3,
+
+    // BLS key set
+    InvalidBlsPublicKey = 4,
+    InvalidProofOfPossession = 5,
+    TooManySigners = 6,
+    InvalidThreshold = 7,
+    InvalidSignerBitmap = 8,
+    ThresholdNotMet = 9,
+
+    // Key rotation
+    KeyRotationPending = 10,
+    NoPendingKeyRotation = 11,
+    KeyRotationNotReady = 12,
+
+    // Passkeys
+    InvalidWebKey = 13,
+    WebKeyNotFound = 14,
+    InvalidWebAuthnData = 15,
+    UserNotPresent = 16,
+    InvalidChallenge = 17,
+    WebKeyAlreadyExists = 18,
+    TooManyWebKeys = 19,
+
+    // Session keys
+    SessionNotAllowed = 20,
+    InvalidSessionKey = 21,
+    SessionKeyNotFound = 22,
+    SessionExpired = 23,
+    SessionTokenNotAllowed = 24,
+    SessionSpendCapExceeded = 25,
+
+    // Spending limits
+    InvalidTokenLimit = 26,
+    SpendLimitExceeded = 27,
+
+    // Guardians and recovery
+    InvalidGuardianConfig = 28,
+    GuardianNotFound = 29,
+    InvalidGuardianProof = 30,
+    GuardiansNotSet = 31,
+    RecoveryPending = 32,
+    NoRecoveryPending = 33,
+    GuardianAlreadyApproved = 34,
+    RecoveryNotReady = 35,
+
+    // Nonces, validity windows and DST
+    InvalidNonceChannel = 36,
+    SignatureNotYetValid = 37,
+    SignatureExpired = 38,
+    DstUpToDate = 39,
+
+    // Auth policies and dual control
+    InvalidAuthPolicy = 40,
+    AuthPolicyNotFound = 41,
+    AuthPolicyNotMet = 42,
+    UnsupportedSignerProof = 43,
+    DualControlRequired = 44,
+
+    // Admin changes
+    AdminChangeNotFound = 45,
+    AdminChangeNotReady = 46,
+
+    // Freeze
+    WalletFrozen = 47,
+    BlsAuthRequired = 48,
+    WalletNotFrozen = 49,
+    NoUnfreezePending = 50,
+    UnfreezeNotReady = 51,
+
+    // Ed25519 and EVM signers
+    Ed25519SignerAlreadyExists = 52,
+    TooManyEd25519Signers = 53,
+    Ed25519SignerNotFound = 54,
+    EvmSignerAlreadyExists = 55,
+    TooManyEvmSigners = 56,
+    EvmSignerNotFound = 57,
+    InvalidRecoveryId = 58,
+
+    // Invocations, address book and tokens
+    InvalidInvokeTarget = 59,
+    AddressNotListed = 60,
+    DestinationNotAllowed = 61,
+    DestinationCoolingOff = 62,
+    TokenNotListed = 63,
+    ContractCreationNotAllowed = 64,
+}
@@ -0,0 +1,103 @@
+use soroban_sdk::{symbol_short, Bytes, BytesN, Env, Map, String, Symbol};
+
+use crate::{
+    data::DataKey,
+    error::ContractError,
+    types::{EvmSigner, Secp256k1Signature},
+};
+
+const MAX_EVM_SIGNERS: u32 = 10;
+// EIP-191 personal_sign prefix for a 32 byte message
+const EIP191_PREFIX: &[u8] = b"\x19Ethereum Signed Message:\n32";
+
+pub fn read_evm_signers(env: &Env) -> Map<BytesN<20>, EvmSigner> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::EvmSigners)
+        .unwrap_or(Map::new(env))
+}
+
+pub fn write_add_evm_signer(
+    env: &Env,
+    address: BytesN<20>,
+    label: String,
+) -> Result<(), ContractError> {
+    let mut signers = read_evm_signers(env);
+    if signers.contains_key(address.clone()) {
+        return Err(ContractError::EvmSignerAlreadyExists);
+    }
+    if signers.len() >= MAX_EVM_SIGNERS {
+        return Err(ContractError::TooManyEvmSigners);
+    }
+
+    let signer = EvmSigner {
+        address: address.clone(),
+        label,
+        created_ledger: env.ledger().sequence(),
+    };
+    signers.set(address.clone(), signer);
+    env.storage()
+        .persistent()
+        .set(&DataKey::EvmSigners, &signers);
+
+    env.events().publish(
+        (Symbol::new(env, "evm_signer"), symbol_short!("added")),
+        address,
+    );
+    Ok(())
+}
+
+pub fn write_remove_evm_signer(env: &Env, address: BytesN<20>) -> Result<(), ContractError> {
+    let mut signers = read_evm_signers(env);
+    if signers.remove(address.clone()).is_none() {
+        return Err(ContractError::EvmSignerNotFound);
+    }
+    env.storage()
+        .persistent()
+        .set(&DataKey::EvmSigners, &signers);
+
+    env.events().publish(
+        (Symbol::new(env, "evm_signer"), symbol_short!("removed")),
+        address,
+    );
+    Ok(())
+}
+
+// The EVM wallet personal_signs the 32 byte owner payload:
+// keccak256("\x19Ethereum Signed Message:\n32" || payload)
+// The recovered key's address, the last 20 bytes of keccak256(x || y), must be registered.
+pub fn verify_secp256k1_signature(
+    env: &Env,
+    payload: &BytesN<32>,
+    tx_signature: Secp256k1Signature,
+) -> Result<(), ContractError> {
+    if !read_evm_signers(env).contains_key(tx_signature.address.clone()) {
+        return Err(ContractError::EvmSignerNotFound);
+    }
+
+    // Wallets report v as 27/28, the host takes the bare recovery id
+    let recovery_id = match tx_signature.recovery_id {
+        0 | 1 => tx_signature.recovery_id,
+        27 | 28 => tx_signature.recovery_id - 27,
+        _ => return Err(ContractError::InvalidRecoveryId),
+    };
+
+    let mut message = Bytes::from_slice(env, EIP191_PREFIX);
+    message.append(&payload.clone().into());
+    let digest = env.crypto().keccak256(&message);
+
+    // Traps the invocation on a malformed or high-S signature
+    let public_key = env
+        .crypto()
+        .secp256k1_recover(&digest, &tx_signature.signature, recovery_id);
+
+    let public_key: Bytes = public_key.into();
+    let key_hash = env.crypto().keccak256(&public_key.slice(1..)).to_array();
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&key_hash[12..]);
+
+    if BytesN::from_array(env, &address) != tx_signature.address {
+        return Err(ContractError::InvalidSignature);
+    }
+    Ok(())
+}
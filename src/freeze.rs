@@ -0,0 +1,122 @@
+use soroban_sdk::{symbol_short, Env, IntoVal, Symbol};
+
+use crate::{
+    bls_account_auth::{increment_nonce, read_nonce, AuthMethod, FREEZE_NONCE_CHANNEL},
+    data::DataKey,
+    error::ContractError,
+    guardians::{read_guardian_config, verify_guardian},
+    types::{FreezeState, GuardianProof},
+};
+
+// Roughly a day of ledgers between an unfreeze request and outflows reopening
+pub const UNFREEZE_DELAY: u32 = 17280;
+
+pub fn read_freeze_state(env: &Env) -> FreezeState {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Freeze)
+        .unwrap_or(FreezeState {
+            frozen: false,
+            epoch: 0,
+            frozen_ledger: 0,
+            unfreeze_after: None,
+        })
+}
+
+fn write_freeze_state(env: &Env, state: &FreezeState) {
+    env.storage().persistent().set(&DataKey::Freeze, state);
+}
+
+fn publish_freeze_event(env: &Env, action: Symbol, state: FreezeState) {
+    env.events()
+        .publish((Symbol::new(env, "freeze"), action), state);
+}
+
+pub fn require_not_frozen(env: &Env) -> Result<(), ContractError> {
+    if read_freeze_state(env).frozen {
+        return Err(ContractError::WalletFrozen);
+    }
+    Ok(())
+}
+
+// Freezing again also drops a pending unfreeze, so whoever still holds a
+// trusted credential can keep the wallet locked against a leaked BLS key.
+// Only owner freezes land here on a frozen wallet, guardian_freeze refuses them.
+pub fn write_freeze(env: &Env) {
+    let mut state = read_freeze_state(env);
+    if !state.frozen {
+        state.frozen_ledger = env.ledger().sequence();
+    }
+    state.frozen = true;
+    state.unfreeze_after = None;
+    write_freeze_state(env, &state);
+
+    publish_freeze_event(env, symbol_short!("frozen"), state);
+}
+
+// A single guardian is enough, signing over the freeze epoch and the freeze nonce so
+// each signature works once. A frozen wallet is left as it is, a guardian can't hold
+// back the owner's pending unfreeze.
+pub fn guardian_freeze(
+    env: &Env,
+    guardian_index: u32,
+    proof: GuardianProof,
+) -> Result<(), ContractError> {
+    let config = read_guardian_config(env).ok_or(ContractError::GuardiansNotSet)?;
+    if read_freeze_state(env).frozen {
+        return Err(ContractError::WalletFrozen);
+    }
+    verify_guardian(
+        env,
+        &config,
+        guardian_index,
+        proof,
+        "guardian_freeze",
+        (
+            guardian_index,
+            read_freeze_state(env).epoch,
+            read_nonce(env, FREEZE_NONCE_CHANNEL),
+        )
+            .into_val(env),
+    )?;
+    increment_nonce(env, FREEZE_NONCE_CHANNEL);
+
+    write_freeze(env);
+    Ok(())
+}
+
+// Unfreezing needs the BLS owner key set, a passkey or the external account alone can't
+pub fn request_unfreeze(env: &Env, auth: &AuthMethod) -> Result<(), ContractError> {
+    match auth {
+        AuthMethod::Bls | AuthMethod::BlsAndUserAccount => {}
+        _ => return Err(ContractError::BlsAuthRequired),
+    }
+
+    let mut state = read_freeze_state(env);
+    if !state.frozen {
+        return Err(ContractError::WalletNotFrozen);
+    }
+    state.unfreeze_after = Some(env.ledger().sequence() + UNFREEZE_DELAY);
+    write_freeze_state(env, &state);
+
+    publish_freeze_event(env, Symbol::new(env, "unfreeze_requested"), state);
+    Ok(())
+}
+
+pub fn execute_unfreeze(env: &Env) -> Result<(), ContractError> {
+    let mut state = read_freeze_state(env);
+    let unfreeze_after = state
+        .unfreeze_after
+        .ok_or(ContractError::NoUnfreezePending)?;
+    if env.ledger().sequence() < unfreeze_after {
+        return Err(ContractError::UnfreezeNotReady);
+    }
+
+    state.frozen = false;
+    state.unfreeze_after = None;
+    state.epoch += 1;
+    write_freeze_state(env, &state);
+
+    publish_freeze_event(env, symbol_short!("unfrozen"), state);
+    Ok(())
+}
@@ -0,0 +1,230 @@
+use soroban_sdk::{
+    crypto::bls12_381::G1Affine, symbol_short, vec, BytesN, Env, IntoVal, Symbol, Val, Vec,
+};
+
+use crate::{
+    access::check_bls_key_set,
+    admin_changes::remove_pending_admin_changes,
+    bls_account_auth::{
+        build_auth_payload, check_bls_pubkey, increment_nonce, verify_bls_pairing,
+        RECOVERY_NONCE_CHANNEL,
+    },
+    data::DataKey,
+    error::ContractError,
+    key_rotation::{apply_key_rotation, read_key_rotation_delay, remove_pending_key_rotation},
+    types::{Guardian, GuardianConfig, GuardianProof, RecoveryRequest},
+    webauthn_account_auth::{cose_to_sec1_pubkey, verify_webauthn_assertion},
+};
+
+// Roughly a day of ledgers, a recovery always leaves the owner time to cancel it
+pub const MIN_RECOVERY_DELAY: u32 = 17280;
+
+pub fn read_guardian_config(env: &Env) -> Option<GuardianConfig> {
+    env.storage().persistent().get(&DataKey::Guardians)
+}
+
+pub fn write_guardian_config(
+    env: &Env,
+    guardians: Vec<Guardian>,
+    threshold: u32,
+    recovery_delay: u32,
+) -> Result<(), ContractError> {
+    if threshold == 0 || threshold > guardians.len() {
+        return Err(ContractError::InvalidGuardianConfig);
+    }
+    // A recovery replaces the key set, it can't be quicker than a rotation the owner queues
+    if recovery_delay < MIN_RECOVERY_DELAY.max(read_key_rotation_delay(env)) {
+        return Err(ContractError::InvalidGuardianConfig);
+    }
+    for guardian in guardians.iter() {
+        match guardian {
+            Guardian::Account(_) => {}
+            Guardian::Bls(pubkey) => {
+                check_bls_pubkey(env, &pubkey)?;
+            }
+            Guardian::Passkey(web_pubkey) => {
+                cose_to_sec1_pubkey(env, &web_pubkey)?;
+            }
+        }
+    }
+
+    let config = GuardianConfig {
+        guardians,
+        threshold,
+        recovery_delay,
+    };
+    env.storage().persistent().set(&DataKey::Guardians, &config);
+
+    // Approvals collected under the previous guardian set no longer mean anything
+    if read_recovery(env).is_some() {
+        publish_recovery_event(env, symbol_short!("cancelled"));
+        remove_recovery(env);
+    }
+    Ok(())
+}
+
+pub fn read_recovery(env: &Env) -> Option<RecoveryRequest> {
+    env.storage().persistent().get(&DataKey::Recovery)
+}
+
+fn write_recovery(env: &Env, recovery: &RecoveryRequest) {
+    env.storage().persistent().set(&DataKey::Recovery, recovery);
+}
+
+fn remove_recovery(env: &Env) {
+    env.storage().persistent().remove(&DataKey::Recovery);
+    increment_nonce(env, RECOVERY_NONCE_CHANNEL);
+}
+
+fn publish_recovery_event(env: &Env, action: Symbol) {
+    env.events()
+        .publish((Symbol::new(env, "recovery"), action), read_recovery(env));
+}
+
+// Guardians sign the owner's payload format on the reserved recovery channel,
+// which advances whenever a recovery ends so its signatures can't be replayed
+pub fn verify_guardian(
+    env: &Env,
+    config: &GuardianConfig,
+    guardian_index: u32,
+    proof: GuardianProof,
+    fn_name: &str,
+    args: Vec<Val>,
+) -> Result<(), ContractError> {
+    let guardian = config
+        .guardians
+        .get(guardian_index)
+        .ok_or(ContractError::GuardianNotFound)?;
+    let payload = build_auth_payload(
+        env,
+        RECOVERY_NONCE_CHANNEL,
+        None,
+        &Symbol::new(env, fn_name),
+        &args,
+    );
+    verify_guardian_proof(env, guardian, proof, &payload)
+}
+
+// Checks a guardian's proof over an already built payload
+pub fn verify_guardian_proof(
+    env: &Env,
+    guardian: Guardian,
+    proof: GuardianProof,
+    payload: &BytesN<32>,
+) -> Result<(), ContractError> {
+    match (guardian, proof) {
+        (Guardian::Account(address), GuardianProof::Account) => {
+            address.require_auth();
+            Ok(())
+        }
+        (Guardian::Bls(pubkey), GuardianProof::Bls(signature)) => verify_bls_pairing(
+            env,
+            G1Affine::from_bytes(pubkey),
+            &payload.clone().into(),
+            signature,
+        ),
+        (Guardian::Passkey(web_pubkey), GuardianProof::Passkey(signature)) => {
+            verify_webauthn_assertion(env, &web_pubkey, payload, &signature)
+        }
+        _ => Err(ContractError::InvalidGuardianProof),
+    }
+}
+
+pub fn start_recovery(
+    env: &Env,
+    guardian_index: u32,
+    proof: GuardianProof,
+    new_pubkeys: Vec<BytesN<96>>,
+    pops: Vec<BytesN<192>>,
+    threshold: u32,
+) -> Result<(), ContractError> {
+    let config = read_guardian_config(env).ok_or(ContractError::GuardiansNotSet)?;
+    if read_recovery(env).is_some() {
+        return Err(ContractError::RecoveryPending);
+    }
+
+    verify_guardian(
+        env,
+        &config,
+        guardian_index,
+        proof,
+        "start_recovery",
+        (guardian_index, new_pubkeys.clone(), pops.clone(), threshold).into_val(env),
+    )?;
+
+    check_bls_key_set(env, &new_pubkeys, &pops)?;
+    if threshold == 0 || threshold > new_pubkeys.len() {
+        return Err(ContractError::InvalidThreshold);
+    }
+
+    let sequence = env.ledger().sequence();
+    let recovery = RecoveryRequest {
+        new_pubkeys,
+        threshold,
+        approvals: vec![env, guardian_index],
+        initiated_ledger: sequence,
+        execute_after: sequence + config.recovery_delay,
+    };
+    write_recovery(env, &recovery);
+    publish_recovery_event(env, symbol_short!("started"));
+    Ok(())
+}
+
+pub fn approve_recovery(
+    env: &Env,
+    guardian_index: u32,
+    proof: GuardianProof,
+) -> Result<(), ContractError> {
+    let config = read_guardian_config(env).ok_or(ContractError::GuardiansNotSet)?;
+    let mut recovery = read_recovery(env).ok_or(ContractError::NoRecoveryPending)?;
+    if recovery.approvals.contains(guardian_index) {
+        return Err(ContractError::GuardianAlreadyApproved);
+    }
+
+    // The approval is for this exact key set, not whatever recovery is open
+    verify_guardian(
+        env,
+        &config,
+        guardian_index,
+        proof,
+        "approve_recovery",
+        (
+            guardian_index,
+            recovery.new_pubkeys.clone(),
+            recovery.threshold,
+        )
+            .into_val(env),
+    )?;
+
+    recovery.approvals.push_back(guardian_index);
+    write_recovery(env, &recovery);
+    publish_recovery_event(env, symbol_short!("approved"));
+    Ok(())
+}
+
+pub fn finalize_recovery(env: &Env) -> Result<(), ContractError> {
+    let config = read_guardian_config(env).ok_or(ContractError::GuardiansNotSet)?;
+    let recovery = read_recovery(env).ok_or(ContractError::NoRecoveryPending)?;
+    if recovery.approvals.len() < config.threshold
+        || env.ledger().sequence() < recovery.execute_after
+    {
+        return Err(ContractError::RecoveryNotReady);
+    }
+
+    publish_recovery_event(env, symbol_short!("finalized"));
+    remove_recovery(env);
+    // Whatever the lost keys had queued is dropped along with them
+    remove_pending_key_rotation(env);
+    remove_pending_admin_changes(env);
+    apply_key_rotation(env, recovery.new_pubkeys, recovery.threshold)
+}
+
+pub fn cancel_recovery(env: &Env) -> Result<(), ContractError> {
+    if read_recovery(env).is_none() {
+        return Err(ContractError::NoRecoveryPending);
+    }
+
+    publish_recovery_event(env, symbol_short!("cancelled"));
+    remove_recovery(env);
+    Ok(())
+}
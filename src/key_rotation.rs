@@ -0,0 +1,155 @@
+use soroban_sdk::{symbol_short, BytesN, Env, Symbol, Vec};
+
+use crate::{
+    access::{check_bls_key_set, write_bls_pubkeys, write_bls_threshold},
+    bls_account_auth::{increment_nonce, NONCE_CHANNELS},
+    data::DataKey,
+    error::ContractError,
+    types::{PendingDelayChange, PendingKeyRotation},
+};
+
+// A lowered delay counts once its own waiting period has passed
+pub fn read_key_rotation_delay(env: &Env) -> u32 {
+    if let Some(pending) = read_pending_key_rotation_delay(env) {
+        if env.ledger().sequence() >= pending.execute_after {
+            return pending.delay_ledgers;
+        }
+    }
+    env.storage()
+        .persistent()
+        .get(&DataKey::KeyRotationDelay)
+        .unwrap_or(0)
+}
+
+pub fn read_pending_key_rotation_delay(env: &Env) -> Option<PendingDelayChange> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::PendingKeyRotationDelay)
+}
+
+// Raising the delay applies at once and drops any queued decrease. Lowering it waits out
+// the current delay, otherwise whoever queues a rotation could zero the delay and skip
+// the cancellation window.
+pub fn write_key_rotation_delay(env: &Env, delay_ledgers: u32) {
+    let current = read_key_rotation_delay(env);
+    if delay_ledgers >= current {
+        env.storage()
+            .persistent()
+            .set(&DataKey::KeyRotationDelay, &delay_ledgers);
+        env.storage()
+            .persistent()
+            .remove(&DataKey::PendingKeyRotationDelay);
+
+        env.events().publish(
+            (Symbol::new(env, "key_rotation"), symbol_short!("delay")),
+            delay_ledgers,
+        );
+        return;
+    }
+
+    let pending = PendingDelayChange {
+        delay_ledgers,
+        execute_after: env.ledger().sequence() + current,
+    };
+    env.storage()
+        .persistent()
+        .set(&DataKey::PendingKeyRotationDelay, &pending);
+
+    env.events().publish(
+        (
+            Symbol::new(env, "key_rotation"),
+            Symbol::new(env, "delay_queued"),
+        ),
+        pending,
+    );
+}
+
+pub fn read_pending_key_rotation(env: &Env) -> Option<PendingKeyRotation> {
+    env.storage().persistent().get(&DataKey::PendingKeyRotation)
+}
+
+pub fn remove_pending_key_rotation(env: &Env) {
+    env.storage()
+        .persistent()
+        .remove(&DataKey::PendingKeyRotation);
+}
+
+pub fn queue_key_rotation(
+    env: &Env,
+    new_pubkeys: Vec<BytesN<96>>,
+    pops: Vec<BytesN<192>>,
+    threshold: u32,
+) -> Result<(), ContractError> {
+    check_bls_key_set(env, &new_pubkeys, &pops)?;
+    if threshold == 0 || threshold > new_pubkeys.len() {
+        return Err(ContractError::InvalidThreshold);
+    }
+
+    let delay = read_key_rotation_delay(env);
+    if delay == 0 {
+        return apply_key_rotation(env, new_pubkeys, threshold);
+    }
+
+    // The old keys have to cancel an open rotation before another can be queued
+    if read_pending_key_rotation(env).is_some() {
+        return Err(ContractError::KeyRotationPending);
+    }
+
+    let pending = PendingKeyRotation {
+        pubkeys: new_pubkeys,
+        threshold,
+        execute_after: env.ledger().sequence() + delay,
+    };
+    env.storage()
+        .persistent()
+        .set(&DataKey::PendingKeyRotation, &pending);
+
+    env.events().publish(
+        (Symbol::new(env, "key_rotation"), symbol_short!("queued")),
+        pending,
+    );
+    Ok(())
+}
+
+pub fn execute_pending_key_rotation(env: &Env) -> Result<(), ContractError> {
+    let pending = read_pending_key_rotation(env).ok_or(ContractError::NoPendingKeyRotation)?;
+    if env.ledger().sequence() < pending.execute_after {
+        return Err(ContractError::KeyRotationNotReady);
+    }
+
+    apply_key_rotation(env, pending.pubkeys, pending.threshold)
+}
+
+pub fn cancel_pending_key_rotation(env: &Env) -> Result<(), ContractError> {
+    let pending = read_pending_key_rotation(env).ok_or(ContractError::NoPendingKeyRotation)?;
+
+    remove_pending_key_rotation(env);
+
+    env.events().publish(
+        (Symbol::new(env, "key_rotation"), symbol_short!("cancelled")),
+        pending,
+    );
+    Ok(())
+}
+
+pub fn apply_key_rotation(
+    env: &Env,
+    new_pubkeys: Vec<BytesN<96>>,
+    threshold: u32,
+) -> Result<(), ContractError> {
+    write_bls_pubkeys(env, new_pubkeys.clone());
+    write_bls_threshold(env, threshold)?;
+    // A rotation queued earlier would otherwise overwrite this one once it matures
+    remove_pending_key_rotation(env);
+    // The auth payload commits to the aggregated key, but a rotation to the same key set
+    // keeps it, so every owner channel moves on as well
+    for channel in 0..NONCE_CHANNELS {
+        increment_nonce(env, channel);
+    }
+
+    env.events().publish(
+        (Symbol::new(env, "key_rotation"), symbol_short!("applied")),
+        (new_pubkeys, threshold),
+    );
+    Ok(())
+}
@@ -3,13 +3,26 @@
 mod access;
 mod account;
 mod account_token;
+mod address_book;
+mod admin_changes;
+mod auth_policy;
 mod bls_account_auth;
 mod constructor;
 mod dap_adapter;
 mod data;
+mod dual_control;
+mod ed25519_signers;
 mod error;
+mod evm_signers;
 mod formatter;
+mod freeze;
+mod guardians;
+mod key_rotation;
+mod operations;
+mod session_keys;
+mod spend_limits;
 mod test;
 mod token_list;
 mod types;
 mod user_account_trait;
+mod webauthn_account_auth;
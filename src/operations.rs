@@ -0,0 +1,312 @@
+use soroban_sdk::{
+    auth::InvokerContractAuthEntry, token, Address, BytesN, Env, Symbol, TryFromVal, Val, Vec,
+};
+
+use crate::{
+    access::read_master_contract_id,
+    account_token::{send_token, write_approve},
+    address_book::require_allowed_destination,
+    bls_account_auth::AuthMethod,
+    dap_adapter::{self, deep_auth_aqua_amm, deep_auth_soroswap},
+    error::ContractError,
+    freeze::require_not_frozen,
+    session_keys::check_session_spend,
+    spend_limits::enforce_token_limit,
+    token_list::{
+        write_add_token, write_remove_token, write_token_activity, write_token_hidden,
+        write_token_metadata,
+    },
+    types::{BatchOp, BatchResult},
+};
+
+// Owner operations shared by their entrypoints and execute_batch, auth is checked by the caller
+
+pub fn run_withdraw(
+    env: &Env,
+    auth: &AuthMethod,
+    to: Address,
+    token_id: Address,
+    amount: i128,
+) -> Result<(), ContractError> {
+    require_allowed_destination(env, &to)?;
+    check_session_spend(env, auth, &token_id, amount)?;
+    enforce_token_limit(env, auth, &token_id, amount)?;
+
+    send_token(env, &to, &token_id, amount);
+    Ok(())
+}
+
+pub fn run_approve(
+    env: &Env,
+    auth: &AuthMethod,
+    token_id: Address,
+    spender: Address,
+    amount: i128,
+) -> Result<(), ContractError> {
+    // An allowance lets the spender pull funds, so it counts against the session cap
+    // and the token limit like a transfer of the same amount, and the spender has to be
+    // an allowed destination
+    require_allowed_destination(env, &spender)?;
+    check_session_spend(env, auth, &token_id, amount)?;
+    enforce_token_limit(env, auth, &token_id, amount)?;
+
+    write_approve(env, &token_id, &spender, &amount);
+    Ok(())
+}
+
+// Returns the amount of the last path token the wallet received
+pub fn run_swap_soroswap(
+    env: &Env,
+    auth: &AuthMethod,
+    amount_in: i128,
+    amount_out_min: i128,
+    path: Vec<Address>,
+    deadline: u64,
+) -> Result<i128, ContractError> {
+    let token_out = path.get_unchecked(path.len() - 1);
+    check_session_spend(env, auth, &path.get_unchecked(0), amount_in)?;
+    check_session_spend(env, auth, &token_out, 0)?;
+    enforce_token_limit(env, auth, &path.get_unchecked(0), amount_in)?;
+
+    let to = env.current_contract_address();
+    let balance_before = token::Client::new(env, &token_out).balance(&to);
+
+    let dapp_adapter_id = read_master_contract_id(env).unwrap();
+    let dapp_adapter_contract = dap_adapter::Client::new(env, &dapp_adapter_id);
+
+    let soroswap_id = dapp_adapter_contract.get_soroswap_id();
+
+    let pair_id = dapp_adapter_contract
+        .get_pair_router_soroswap(&path.get_unchecked(0), &path.get_unchecked(1));
+
+    deep_auth_soroswap(
+        env.clone(),
+        soroswap_id,
+        pair_id,
+        amount_in,
+        amount_out_min,
+        deadline,
+        path.clone(),
+        to.clone(),
+    );
+
+    dapp_adapter_contract.swap_exact_soroswap(&amount_in, &amount_out_min, &path, &to, &deadline);
+
+    write_add_token(env.clone(), token_out.clone());
+    write_token_activity(env, &path.get_unchecked(0));
+    write_token_activity(env, &token_out);
+
+    Ok(token::Client::new(env, &token_out).balance(&to) - balance_before)
+}
+
+// Returns the amount of the final token_out the wallet received
+pub fn run_swap_aqua(
+    env: &Env,
+    auth: &AuthMethod,
+    swaps_chain: Vec<(Vec<Address>, BytesN<32>, Address)>,
+    token_in: Address,
+    in_amount: u128,
+    out_min: u128,
+) -> Result<i128, ContractError> {
+    check_session_spend(env, auth, &token_in, in_amount as i128)?;
+    let (_, _, token_out) = swaps_chain.get_unchecked(swaps_chain.len() - 1);
+    check_session_spend(env, auth, &token_out, 0)?;
+    enforce_token_limit(env, auth, &token_in, in_amount as i128)?;
+
+    let to = env.current_contract_address();
+    let balance_before = token::Client::new(env, &token_out).balance(&to);
+
+    let dapp_adapter_id = read_master_contract_id(env).unwrap();
+    let dapp_adapter_contract = dap_adapter::Client::new(env, &dapp_adapter_id);
+
+    let aqua_router_id = dapp_adapter_contract.get_aqua_amm_router_id();
+
+    deep_auth_aqua_amm(
+        env.clone(),
+        aqua_router_id,
+        to.clone(),
+        swaps_chain.clone(),
+        token_in.clone(),
+        in_amount,
+        out_min,
+    );
+
+    dapp_adapter_contract.swap_chain_aqua(&to, &swaps_chain, &token_in, &in_amount, &out_min);
+    write_add_token(env.clone(), token_out.clone());
+    write_token_activity(env, &token_in);
+    write_token_activity(env, &token_out);
+
+    Ok(token::Client::new(env, &token_out).balance(&to) - balance_before)
+}
+
+// Listing a token explicitly caches its metadata and brings it back if it was hidden
+pub fn run_add_token(env: &Env, token_id: Address) -> Result<(), ContractError> {
+    write_add_token(env.clone(), token_id.clone());
+    write_token_metadata(env, &token_id);
+    write_token_hidden(env, &token_id, false)
+}
+
+// Token calls that move funds out of `from`, by arity and argument position:
+// (fn, arg count, from, destination, amount). transfer_from and burn_from only draw on an
+// allowance, which was charged when it was approved.
+const TOKEN_OUTFLOWS: [(&str, u32, u32, Option<u32>, u32); 3] = [
+    ("transfer", 3, 0, Some(1), 2),
+    ("approve", 4, 0, Some(1), 2),
+    ("burn", 2, 0, None, 1),
+];
+
+// Token outflows from the wallet anywhere in the call tree count against its limits like a
+// withdraw, an approve against the spender as destination. Direct calls need no auth entry,
+// so the top level call is checked along with the tree.
+pub(crate) fn enforce_invocation_limits(
+    env: &Env,
+    auth: &AuthMethod,
+    contract: &Address,
+    fn_name: &Symbol,
+    args: &Vec<Val>,
+) -> Result<(), ContractError> {
+    let Some((_, _, _, to_at, amount_at)) = find_wallet_outflow(env, fn_name, args) else {
+        return Ok(());
+    };
+
+    if let Some(to_at) = to_at {
+        let to = Address::try_from_val(env, &args.get_unchecked(*to_at))
+            .map_err(|_| ContractError::InvalidInvokeTarget)?;
+        require_allowed_destination(env, &to)?;
+    }
+    let amount = i128::try_from_val(env, &args.get_unchecked(*amount_at))
+        .map_err(|_| ContractError::InvalidInvokeTarget)?;
+    check_session_spend(env, auth, contract, amount)?;
+    enforce_token_limit(env, auth, contract, amount)
+}
+
+fn find_wallet_outflow(
+    env: &Env,
+    fn_name: &Symbol,
+    args: &Vec<Val>,
+) -> Option<&'static (&'static str, u32, u32, Option<u32>, u32)> {
+    let outflow = TOKEN_OUTFLOWS.iter().find(|(name, arg_count, ..)| {
+        Symbol::new(env, name) == *fn_name && args.len() == *arg_count
+    })?;
+    match Address::try_from_val(env, &args.get_unchecked(outflow.2)) {
+        Ok(from) if from == env.current_contract_address() => Some(outflow),
+        _ => None,
+    }
+}
+
+// Whether the call moves the wallet's own funds, such calls answer to the withdraw policy
+pub(crate) fn is_wallet_outflow(env: &Env, fn_name: &Symbol, args: &Vec<Val>) -> bool {
+    find_wallet_outflow(env, fn_name, args).is_some()
+}
+
+fn enforce_auth_entry_limits(
+    env: &Env,
+    auth: &AuthMethod,
+    auth_entries: &Vec<InvokerContractAuthEntry>,
+) -> Result<(), ContractError> {
+    for entry in auth_entries.iter() {
+        if let InvokerContractAuthEntry::Contract(invocation) = entry {
+            let context = invocation.context;
+            enforce_invocation_limits(
+                env,
+                auth,
+                &context.contract,
+                &context.fn_name,
+                &context.args,
+            )?;
+            enforce_auth_entry_limits(env, auth, &invocation.sub_invocations)?;
+        }
+    }
+    Ok(())
+}
+
+// Calls any contract as the wallet, with the supplied auth tree pre-authorized for the call
+pub fn run_invoke(
+    env: &Env,
+    auth: &AuthMethod,
+    contract: Address,
+    fn_name: Symbol,
+    args: Vec<Val>,
+    auth_entries: Vec<InvokerContractAuthEntry>,
+) -> Result<Val, ContractError> {
+    // Calls back into the wallet would skip its own entrypoint checks
+    if contract == env.current_contract_address() {
+        return Err(ContractError::InvalidInvokeTarget);
+    }
+
+    enforce_invocation_limits(env, auth, &contract, &fn_name, &args)?;
+    enforce_auth_entry_limits(env, auth, &auth_entries)?;
+
+    if !auth_entries.is_empty() {
+        env.authorize_as_current_contract(auth_entries);
+    }
+    Ok(env.invoke_contract::<Val>(&contract, &fn_name, args))
+}
+
+// The entrypoint each op stands in for, its auth policy and dual control setting apply to the batch
+pub fn batch_op_fn(env: &Env, op: &BatchOp) -> Symbol {
+    let fn_name = match op {
+        BatchOp::Withdraw(..) => "withdraw",
+        BatchOp::Approve(..) => "approve",
+        BatchOp::SwapSoroswap(..) => "swap_tokens_soroswap",
+        BatchOp::SwapAqua(..) => "swap_tokens_aqua",
+        BatchOp::AddToken(_) => "add_token",
+        BatchOp::RemoveToken(_) => "remove_token",
+    };
+    Symbol::new(env, fn_name)
+}
+
+// Runs the ops in order, the first failure reverts the whole invocation
+pub fn run_batch(
+    env: &Env,
+    auth: &AuthMethod,
+    ops: Vec<BatchOp>,
+) -> Result<Vec<BatchResult>, ContractError> {
+    let mut results = Vec::new(env);
+    for op in ops.iter() {
+        let result = match op {
+            BatchOp::Withdraw(to, token_id, amount) => {
+                require_not_frozen(env)?;
+                run_withdraw(env, auth, to, token_id, amount)?;
+                BatchResult::Done
+            }
+            BatchOp::Approve(token_id, spender, amount) => {
+                require_not_frozen(env)?;
+                run_approve(env, auth, token_id, spender, amount)?;
+                BatchResult::Done
+            }
+            BatchOp::SwapSoroswap(amount_in, amount_out_min, path, deadline) => {
+                require_not_frozen(env)?;
+                BatchResult::Swapped(run_swap_soroswap(
+                    env,
+                    auth,
+                    amount_in,
+                    amount_out_min,
+                    path,
+                    deadline,
+                )?)
+            }
+            BatchOp::SwapAqua(swaps_chain, token_in, in_amount, out_min) => {
+                require_not_frozen(env)?;
+                BatchResult::Swapped(run_swap_aqua(
+                    env,
+                    auth,
+                    swaps_chain,
+                    token_in,
+                    in_amount,
+                    out_min,
+                )?)
+            }
+            BatchOp::AddToken(token_id) => {
+                run_add_token(env, token_id)?;
+                BatchResult::Done
+            }
+            BatchOp::RemoveToken(token_id) => {
+                write_remove_token(env.clone(), token_id)?;
+                BatchResult::Done
+            }
+        };
+        results.push_back(result);
+    }
+    Ok(results)
+}
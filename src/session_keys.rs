@@ -0,0 +1,132 @@
+use soroban_sdk::{Address, BytesN, Env, Map, Symbol, Vec};
+
+use crate::{
+    bls_account_auth::AuthMethod,
+    data::DataKey,
+    error::ContractError,
+    types::{SessionKey, SessionSignature},
+};
+
+// Entrypoints a session key may be scoped to, everything else needs the owner
+const SESSION_CALLABLE_FNS: [&str; 4] = [
+    "withdraw",
+    "approve",
+    "swap_tokens_soroswap",
+    "swap_tokens_aqua",
+];
+
+pub fn read_session_keys(env: &Env) -> Map<BytesN<32>, SessionKey> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::SessionKeys)
+        .unwrap_or(Map::new(env))
+}
+
+fn write_session_keys(env: &Env, session_keys: &Map<BytesN<32>, SessionKey>) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::SessionKeys, session_keys);
+}
+
+pub fn write_add_session_key(
+    env: &Env,
+    public_key: BytesN<32>,
+    expiry_ledger: u32,
+    allowed_fns: Vec<Symbol>,
+    allowed_tokens: Vec<Address>,
+    spend_cap: i128,
+) -> Result<(), ContractError> {
+    if expiry_ledger <= env.ledger().sequence() || spend_cap < 0 {
+        return Err(ContractError::InvalidSessionKey);
+    }
+    for fn_name in allowed_fns.iter() {
+        let callable = SESSION_CALLABLE_FNS
+            .iter()
+            .any(|name| Symbol::new(env, name) == fn_name);
+        if !callable {
+            return Err(ContractError::SessionNotAllowed);
+        }
+    }
+
+    let mut session_keys = read_session_keys(env);
+    let session_key = SessionKey {
+        public_key: public_key.clone(),
+        expiry_ledger,
+        allowed_fns,
+        allowed_tokens,
+        spend_cap,
+        spent: 0,
+    };
+    session_keys.set(public_key, session_key);
+    write_session_keys(env, &session_keys);
+    Ok(())
+}
+
+pub fn write_revoke_session_key(env: &Env, public_key: BytesN<32>) -> Result<(), ContractError> {
+    let mut session_keys = read_session_keys(env);
+    if session_keys.remove(public_key).is_none() {
+        return Err(ContractError::SessionKeyNotFound);
+    }
+    write_session_keys(env, &session_keys);
+    Ok(())
+}
+
+pub fn verify_session_signature(
+    env: &Env,
+    payload: &BytesN<32>,
+    fn_name: &Symbol,
+    tx_signature: SessionSignature,
+) -> Result<(), ContractError> {
+    let session_key = read_session_keys(env)
+        .get(tx_signature.public_key.clone())
+        .ok_or(ContractError::SessionKeyNotFound)?;
+
+    if env.ledger().sequence() > session_key.expiry_ledger {
+        return Err(ContractError::SessionExpired);
+    }
+    if !session_key.allowed_fns.contains(fn_name) {
+        return Err(ContractError::SessionNotAllowed);
+    }
+
+    // Traps the invocation if the signature doesn't match
+    env.crypto().ed25519_verify(
+        &tx_signature.public_key,
+        &payload.clone().into(),
+        &tx_signature.signature,
+    );
+    Ok(())
+}
+
+// Charges a token movement against the session that authorized it, a no-op for owner auth
+pub fn check_session_spend(
+    env: &Env,
+    auth: &AuthMethod,
+    token_id: &Address,
+    amount: i128,
+) -> Result<(), ContractError> {
+    let AuthMethod::Session(public_key) = auth else {
+        return Ok(());
+    };
+
+    let mut session_keys = read_session_keys(env);
+    let mut session_key = session_keys
+        .get(public_key.clone())
+        .ok_or(ContractError::SessionKeyNotFound)?;
+
+    if !session_key.allowed_tokens.contains(token_id) {
+        return Err(ContractError::SessionTokenNotAllowed);
+    }
+
+    let spent = session_key
+        .spent
+        .checked_add(amount)
+        .ok_or(ContractError::SessionSpendCapExceeded)?;
+    if spent > session_key.spend_cap {
+        return Err(ContractError::SessionSpendCapExceeded);
+    }
+
+    session_key.spent = spent;
+    session_keys.set(public_key.clone(), session_key);
+    write_session_keys(env, &session_keys);
+    Ok(())
+}
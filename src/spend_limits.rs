@@ -0,0 +1,110 @@
+use soroban_sdk::{Address, Env, Map};
+
+use crate::{
+    access::read_user_account,
+    bls_account_auth::AuthMethod,
+    data::DataKey,
+    error::ContractError,
+    types::{TokenLimit, TokenSpendWindow},
+};
+
+// The window is tracked in this many buckets and rolls forward one bucket at a time
+const SPEND_WINDOW_BUCKETS: u32 = 24;
+
+pub fn read_token_limit(env: &Env, token_id: &Address) -> Option<TokenLimit> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::TokenLimit(token_id.clone()))
+}
+
+pub fn write_token_limit(
+    env: &Env,
+    token_id: &Address,
+    limit: &TokenLimit,
+) -> Result<(), ContractError> {
+    if limit.per_tx < 0 || limit.per_window < 0 || limit.window_ledgers == 0 {
+        return Err(ContractError::InvalidTokenLimit);
+    }
+    env.storage()
+        .persistent()
+        .set(&DataKey::TokenLimit(token_id.clone()), limit);
+    Ok(())
+}
+
+pub fn write_remove_token_limit(env: &Env, token_id: &Address) {
+    env.storage()
+        .persistent()
+        .remove(&DataKey::TokenLimit(token_id.clone()));
+    env.storage()
+        .persistent()
+        .remove(&DataKey::TokenSpendWindow(token_id.clone()));
+}
+
+pub fn read_token_spend_window(env: &Env, token_id: &Address) -> TokenSpendWindow {
+    env.storage()
+        .persistent()
+        .get(&DataKey::TokenSpendWindow(token_id.clone()))
+        .unwrap_or(TokenSpendWindow {
+            buckets: Map::new(env),
+        })
+}
+
+// A limit is looser when it lets more through, or resets more often
+pub fn is_looser_limit(current: &TokenLimit, new: &TokenLimit) -> bool {
+    new.per_tx > current.per_tx
+        || new.per_window > current.per_window
+        || new.window_ledgers < current.window_ledgers
+}
+
+// Above-limit operations need the BLS owner and the linked external account together
+pub fn require_elevated_auth(env: &Env, auth: &AuthMethod) -> Result<(), ContractError> {
+    match auth {
+        AuthMethod::Bls => {
+            read_user_account(env)?.require_auth();
+            Ok(())
+        }
+        // The policy already had the external account sign
+        AuthMethod::BlsAndUserAccount => Ok(()),
+        _ => Err(ContractError::SpendLimitExceeded),
+    }
+}
+
+pub fn enforce_token_limit(
+    env: &Env,
+    auth: &AuthMethod,
+    token_id: &Address,
+    amount: i128,
+) -> Result<(), ContractError> {
+    let Some(limit) = read_token_limit(env, token_id) else {
+        return Ok(());
+    };
+
+    let sequence = env.ledger().sequence();
+    let bucket_ledgers = limit.window_ledgers.div_ceil(SPEND_WINDOW_BUCKETS);
+    let current_bucket = sequence - sequence % bucket_ledgers;
+    let window_floor = sequence.saturating_sub(limit.window_ledgers);
+
+    // Every bucket that overlaps the last window_ledgers ledgers still counts, so the
+    // window never lets more than per_window through, whatever the timing
+    let mut buckets = Map::new(env);
+    let mut spent: i128 = amount;
+    for (bucket_start, bucket_spent) in read_token_spend_window(env, token_id).buckets.iter() {
+        if bucket_start.saturating_add(bucket_ledgers) > window_floor {
+            buckets.set(bucket_start, bucket_spent);
+            spent = spent
+                .checked_add(bucket_spent)
+                .ok_or(ContractError::SpendLimitExceeded)?;
+        }
+    }
+    if amount > limit.per_tx || spent > limit.per_window {
+        require_elevated_auth(env, auth)?;
+    }
+
+    let bucket_spent = buckets.get(current_bucket).unwrap_or(0);
+    buckets.set(current_bucket, bucket_spent + amount);
+    let window = TokenSpendWindow { buckets };
+    env.storage()
+        .persistent()
+        .set(&DataKey::TokenSpendWindow(token_id.clone()), &window);
+    Ok(())
+}
@@ -1,2 +1,634 @@
 #![cfg(test)]
 extern crate std;
+
+use socketfi_bls_signer::{
+    aggregate_pubkeys, aggregate_signatures, auth_dst, signer_bitmap, AuthPayload, PublicKey,
+    SecretKey, ValidityKind as SignerValidityKind, ValidityWindow as SignerValidityWindow,
+    AUTH_DST_VERSION,
+};
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    token::{StellarAssetClient, TokenClient},
+    vec,
+    xdr::{ScAddress, ScVal},
+    Address, Bytes, BytesN, Env, IntoVal, String, Symbol, Val, Vec,
+};
+use std::vec::Vec as StdVec;
+
+use crate::{
+    access::{read_bls_pubkeys, read_master_contract_id, write_user_account},
+    account::{Account, AccountClient},
+    admin_changes::DEFAULT_ADMIN_CHANGE_DELAY,
+    bls_account_auth::{owner_require_auth, read_nonce},
+    data::DataKey,
+    error::ContractError,
+    guardians::{write_guardian_config, MIN_RECOVERY_DELAY},
+    key_rotation::{
+        cancel_pending_key_rotation, execute_pending_key_rotation, queue_key_rotation,
+        read_key_rotation_delay, read_pending_key_rotation, read_pending_key_rotation_delay,
+        write_key_rotation_delay,
+    },
+    types::{
+        AdminChange, BlsSignature, Guardian, GuardianProof, SignerProof, TxSignature, ValidityKind,
+        ValidityWindow,
+    },
+    webauthn_account_auth::{base64url_encode_challenge, cose_to_sec1_pubkey},
+};
+
+fn cose_web_pubkey(env: &Env) -> BytesN<77> {
+    let mut cose = [0u8; 77];
+    cose[..10].copy_from_slice(&[0xa5, 0x01, 0x02, 0x03, 0x26, 0x20, 0x01, 0x21, 0x58, 0x20]);
+    cose[42..45].copy_from_slice(&[0x22, 0x58, 0x20]);
+    BytesN::from_array(env, &cose)
+}
+
+fn bls_key_set(env: &Env, secret_keys: &[SecretKey]) -> (Vec<BytesN<96>>, Vec<BytesN<192>>) {
+    let mut pubkeys = Vec::new(env);
+    let mut pops = Vec::new(env);
+    for sk in secret_keys {
+        pubkeys.push_back(BytesN::from_array(env, &sk.public_key().to_bytes()));
+        pops.push_back(BytesN::from_array(
+            env,
+            &sk.proof_of_possession().to_bytes(),
+        ));
+    }
+    (pubkeys, pops)
+}
+
+fn setup(env: &Env, secret_keys: &[SecretKey], threshold: u32) -> Address {
+    // PoP and signature checks are pairing heavy, keep them out of the budget
+    env.cost_estimate().budget().reset_unlimited();
+
+    let contract_id = env.register(Account, ());
+    let client = AccountClient::new(env, &contract_id);
+
+    let (pubkeys, pops) = bls_key_set(env, secret_keys);
+    client.init(
+        &pubkeys,
+        &pops,
+        &threshold,
+        &String::from_str(env, "Google"),
+        &String::from_str(env, "Alice"),
+        &cose_web_pubkey(env),
+        &Bytes::from_slice(env, b"credential-0"),
+        &Address::generate(env),
+        &Address::generate(env),
+    );
+    contract_id
+}
+
+fn validity_window(env: &Env) -> SignerValidityWindow {
+    let sequence = env.ledger().sequence() as u64;
+    SignerValidityWindow {
+        kind: SignerValidityKind::Ledger,
+        valid_after: sequence,
+        valid_until: sequence + 100,
+    }
+}
+
+fn signed_tx(
+    env: &Env,
+    contract_id: &Address,
+    secret_keys: &[SecretKey],
+    signers: &[u32],
+    fn_name: &str,
+    args: StdVec<ScVal>,
+) -> TxSignature {
+    let all_pubkeys: StdVec<PublicKey> = secret_keys.iter().map(|sk| sk.public_key()).collect();
+    let validity = validity_window(env);
+    let payload = AuthPayload {
+        channel: 0,
+        nonce: env.as_contract(contract_id, || read_nonce(env, 0)),
+        aggregated_pubkey: aggregate_pubkeys(&all_pubkeys).unwrap(),
+        wallet: ScAddress::from(contract_id),
+        network_id: env.ledger().network_id().to_array(),
+        fn_name: fn_name.into(),
+        args,
+        validity: Some(validity),
+    }
+    .hash()
+    .unwrap();
+
+    let dst = auth_dst(AUTH_DST_VERSION, &env.ledger().network_id().to_array());
+    let signatures: StdVec<_> = signers
+        .iter()
+        .map(|i| secret_keys[*i as usize].sign_payload(&payload, &dst))
+        .collect();
+    let signature = aggregate_signatures(&signatures).unwrap();
+
+    TxSignature {
+        channel: 0,
+        validity: ValidityWindow {
+            kind: ValidityKind::Ledger,
+            valid_after: validity.valid_after,
+            valid_until: validity.valid_until,
+        },
+        proofs: vec![
+            env,
+            SignerProof::Bls(BlsSignature {
+                signers: signer_bitmap(signers),
+                signature: BytesN::from_array(env, &signature.to_bytes()),
+            }),
+        ],
+    }
+}
+
+fn secret_keys() -> [SecretKey; 3] {
+    [
+        SecretKey::from_seed(b"device"),
+        SecretKey::from_seed(b"cloud"),
+        SecretKey::from_seed(b"recovery"),
+    ]
+}
+
+#[test]
+fn test_signer_full_key_set_passes_owner_auth() {
+    let env = Env::default();
+    let secret_keys = secret_keys();
+    let contract_id = setup(&env, &secret_keys, 3);
+
+    let tx_signature = signed_tx(
+        &env,
+        &contract_id,
+        &secret_keys,
+        &[0, 1, 2],
+        "set_key_rotation_delay",
+        std::vec![ScVal::U32(100)],
+    );
+
+    env.as_contract(&contract_id, || {
+        let args: Vec<Val> = (100u32,).into_val(&env);
+        let fn_name = Symbol::new(&env, "set_key_rotation_delay");
+        assert!(owner_require_auth(env.clone(), fn_name, args, Some(tx_signature)).is_ok());
+        assert_eq!(read_nonce(&env, 0), 1);
+    });
+}
+
+#[test]
+fn test_signer_threshold_subset_passes_owner_auth() {
+    let env = Env::default();
+    let secret_keys = secret_keys();
+    let contract_id = setup(&env, &secret_keys, 2);
+
+    let tx_signature = signed_tx(
+        &env,
+        &contract_id,
+        &secret_keys,
+        &[0, 2],
+        "set_key_rotation_delay",
+        std::vec![ScVal::U32(100)],
+    );
+
+    env.as_contract(&contract_id, || {
+        let args: Vec<Val> = (100u32,).into_val(&env);
+        let fn_name = Symbol::new(&env, "set_key_rotation_delay");
+        assert!(owner_require_auth(env.clone(), fn_name, args, Some(tx_signature)).is_ok());
+    });
+}
+
+#[test]
+fn test_signer_below_threshold_fails_owner_auth() {
+    let env = Env::default();
+    let secret_keys = secret_keys();
+    let contract_id = setup(&env, &secret_keys, 2);
+
+    let tx_signature = signed_tx(
+        &env,
+        &contract_id,
+        &secret_keys,
+        &[1],
+        "set_key_rotation_delay",
+        std::vec![ScVal::U32(100)],
+    );
+
+    env.as_contract(&contract_id, || {
+        let args: Vec<Val> = (100u32,).into_val(&env);
+        let fn_name = Symbol::new(&env, "set_key_rotation_delay");
+        let result = owner_require_auth(env.clone(), fn_name, args, Some(tx_signature));
+        assert_eq!(result.err(), Some(ContractError::ThresholdNotMet));
+    });
+}
+
+#[test]
+fn test_signer_signature_is_bound_to_the_operation() {
+    let env = Env::default();
+    let secret_keys = secret_keys();
+    let contract_id = setup(&env, &secret_keys, 3);
+
+    let tx_signature = signed_tx(
+        &env,
+        &contract_id,
+        &secret_keys,
+        &[0, 1, 2],
+        "set_key_rotation_delay",
+        std::vec![ScVal::U32(100)],
+    );
+
+    env.as_contract(&contract_id, || {
+        // Same signature replayed with different arguments
+        let args: Vec<Val> = vec![&env, 0u32.into_val(&env)];
+        let fn_name = Symbol::new(&env, "set_key_rotation_delay");
+        let result = owner_require_auth(env.clone(), fn_name, args, Some(tx_signature));
+        assert_eq!(result.err(), Some(ContractError::InvalidSignature));
+        assert_eq!(read_nonce(&env, 0), 0);
+    });
+}
+
+#[test]
+fn test_webauthn_challenge_encodes_as_unpadded_base64url() {
+    let mut challenge = [0u8; 32];
+    for (i, byte) in challenge.iter_mut().enumerate() {
+        *byte = i as u8;
+    }
+    assert_eq!(
+        &base64url_encode_challenge(&challenge),
+        b"AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8"
+    );
+    // 0xff runs hit the url safe alphabet
+    assert_eq!(
+        &base64url_encode_challenge(&[0xff; 32]),
+        b"__________________________________________8"
+    );
+}
+
+#[test]
+fn test_cose_key_with_wrong_header_is_rejected() {
+    let env = Env::default();
+    assert!(cose_to_sec1_pubkey(&env, &cose_web_pubkey(&env)).is_ok());
+
+    // alg -8 (EdDSA) instead of -7 (ES256)
+    let mut cose = cose_web_pubkey(&env).to_array();
+    cose[4] = 0x27;
+    assert_eq!(
+        cose_to_sec1_pubkey(&env, &BytesN::from_array(&env, &cose)),
+        Err(ContractError::InvalidWebKey)
+    );
+
+    // y coordinate label shifted out of place
+    let mut cose = cose_web_pubkey(&env).to_array();
+    cose[42] = 0x23;
+    assert_eq!(
+        cose_to_sec1_pubkey(&env, &BytesN::from_array(&env, &cose)),
+        Err(ContractError::InvalidWebKey)
+    );
+}
+
+fn advance_ledgers(env: &Env, ledgers: u32) {
+    env.ledger()
+        .with_mut(|ledger| ledger.sequence_number += ledgers);
+}
+
+// Owner calls without a signature fall back to the linked account, mocked in these tests
+fn setup_with_user_account(env: &Env) -> (Address, AccountClient<'_>) {
+    env.mock_all_auths();
+    let contract_id = setup(env, &secret_keys(), 3);
+    env.as_contract(&contract_id, || {
+        write_user_account(env, &Address::generate(env));
+    });
+    let client = AccountClient::new(env, &contract_id);
+    (contract_id, client)
+}
+
+#[test]
+fn test_admin_change_is_queued_behind_the_default_delay() {
+    let env = Env::default();
+    let (contract_id, client) = setup_with_user_account(&env);
+
+    let master_contract = Address::generate(&env);
+    client.update_master_contract(&master_contract, &None);
+
+    let pending = client.get_pending_changes();
+    assert_eq!(pending.len(), 1);
+    let change = pending.get_unchecked(0);
+    assert!(matches!(
+        change.change,
+        AdminChange::MasterContract(ref address) if *address == master_contract
+    ));
+    assert_eq!(
+        change.execute_after,
+        env.ledger().sequence() + DEFAULT_ADMIN_CHANGE_DELAY
+    );
+    env.as_contract(&contract_id, || {
+        assert_ne!(read_master_contract_id(&env), Some(master_contract.clone()));
+    });
+}
+
+#[test]
+fn test_admin_change_executes_only_after_the_delay() {
+    let env = Env::default();
+    let (contract_id, client) = setup_with_user_account(&env);
+
+    let master_contract = Address::generate(&env);
+    client.update_master_contract(&master_contract, &None);
+    let id = client.get_pending_changes().get_unchecked(0).id;
+
+    advance_ledgers(&env, DEFAULT_ADMIN_CHANGE_DELAY - 1);
+    assert_eq!(
+        client.try_execute_admin_change(&id),
+        Err(Ok(ContractError::AdminChangeNotReady))
+    );
+
+    advance_ledgers(&env, 1);
+    client.execute_admin_change(&id);
+    assert!(client.get_pending_changes().is_empty());
+    env.as_contract(&contract_id, || {
+        assert_eq!(read_master_contract_id(&env), Some(master_contract));
+    });
+}
+
+#[test]
+fn test_cancelled_admin_change_is_dropped() {
+    let env = Env::default();
+    let (_, client) = setup_with_user_account(&env);
+
+    client.update_master_contract(&Address::generate(&env), &None);
+    client.update_dapp_router_contract(&Address::generate(&env), &None);
+    let pending = client.get_pending_changes();
+    assert_eq!(pending.len(), 2);
+    let id = pending.get_unchecked(0).id;
+
+    client.cancel_admin_change(&id, &None);
+    let pending = client.get_pending_changes();
+    assert_eq!(pending.len(), 1);
+    assert_ne!(pending.get_unchecked(0).id, id);
+
+    assert_eq!(
+        client.try_cancel_admin_change(&id, &None),
+        Err(Ok(ContractError::AdminChangeNotFound))
+    );
+    advance_ledgers(&env, DEFAULT_ADMIN_CHANGE_DELAY);
+    assert_eq!(
+        client.try_execute_admin_change(&id),
+        Err(Ok(ContractError::AdminChangeNotFound))
+    );
+}
+
+#[test]
+fn test_guardians_need_the_bls_key_set() {
+    let env = Env::default();
+    let (_, client) = setup_with_user_account(&env);
+
+    let guardians = vec![&env, Guardian::Account(Address::generate(&env))];
+    assert_eq!(
+        client.try_set_guardians(&guardians, &1, &MIN_RECOVERY_DELAY, &None),
+        Err(Ok(ContractError::BlsAuthRequired))
+    );
+}
+
+#[test]
+fn test_guardian_recovery_delay_has_a_floor() {
+    let env = Env::default();
+    let (contract_id, _) = setup_with_user_account(&env);
+
+    let guardians = vec![&env, Guardian::Account(Address::generate(&env))];
+    env.as_contract(&contract_id, || {
+        assert_eq!(
+            write_guardian_config(&env, guardians.clone(), 1, 0),
+            Err(ContractError::InvalidGuardianConfig)
+        );
+        assert_eq!(
+            write_guardian_config(&env, guardians.clone(), 1, MIN_RECOVERY_DELAY),
+            Ok(())
+        );
+    });
+}
+
+#[test]
+fn test_approved_allowance_is_charged_to_the_limit_once() {
+    let env = Env::default();
+    let (contract_id, client) = setup_with_user_account(&env);
+
+    let token_id = register_token(&env, &contract_id, 1_000);
+    client.set_token_limit(&token_id, &100, &100, &17280, &None);
+
+    let spender = Address::generate(&env);
+    let to = Address::generate(&env);
+    client.approve(&token_id, &spender, &100, &None);
+    client.spend(&token_id, &spender, &100, &to);
+    assert_eq!(TokenClient::new(&env, &token_id).balance(&to), 100);
+
+    // The window is used up by the approval, a second one needs elevated auth
+    assert_eq!(
+        client.try_approve(&token_id, &spender, &1, &None),
+        Err(Ok(ContractError::SpendLimitExceeded))
+    );
+}
+
+#[test]
+fn test_guardian_freeze_leaves_a_frozen_wallet_alone() {
+    let env = Env::default();
+    let (contract_id, client) = setup_with_user_account(&env);
+
+    let guardians = vec![&env, Guardian::Account(Address::generate(&env))];
+    env.as_contract(&contract_id, || {
+        write_guardian_config(&env, guardians, 1, MIN_RECOVERY_DELAY).unwrap();
+    });
+
+    client.guardian_freeze(&0, &GuardianProof::Account);
+    assert!(client.get_freeze_state().frozen);
+    assert_eq!(
+        client.try_guardian_freeze(&0, &GuardianProof::Account),
+        Err(Ok(ContractError::WalletFrozen))
+    );
+}
+
+fn new_secret_keys() -> [SecretKey; 2] {
+    [
+        SecretKey::from_seed(b"new device"),
+        SecretKey::from_seed(b"new cloud"),
+    ]
+}
+
+#[test]
+fn test_key_rotation_is_queued_until_cancelled() {
+    let env = Env::default();
+    let secret_keys = secret_keys();
+    let contract_id = setup(&env, &secret_keys, 3);
+    let (new_pubkeys, new_pops) = bls_key_set(&env, &new_secret_keys());
+
+    env.as_contract(&contract_id, || {
+        let current_pubkeys = read_bls_pubkeys(&env);
+        write_key_rotation_delay(&env, 100);
+        queue_key_rotation(&env, new_pubkeys.clone(), new_pops.clone(), 2).unwrap();
+
+        let pending = read_pending_key_rotation(&env).unwrap();
+        assert_eq!(pending.pubkeys, new_pubkeys);
+        assert_eq!(pending.execute_after, env.ledger().sequence() + 100);
+        assert_eq!(read_bls_pubkeys(&env), current_pubkeys);
+        assert_eq!(
+            execute_pending_key_rotation(&env),
+            Err(ContractError::KeyRotationNotReady)
+        );
+
+        cancel_pending_key_rotation(&env).unwrap();
+        assert!(read_pending_key_rotation(&env).is_none());
+        assert_eq!(
+            cancel_pending_key_rotation(&env),
+            Err(ContractError::NoPendingKeyRotation)
+        );
+        assert_eq!(read_bls_pubkeys(&env), current_pubkeys);
+    });
+}
+
+#[test]
+fn test_lowering_the_rotation_delay_waits_out_the_current_one() {
+    let env = Env::default();
+    let contract_id = setup(&env, &secret_keys(), 3);
+
+    env.as_contract(&contract_id, || {
+        write_key_rotation_delay(&env, 100);
+        assert_eq!(read_key_rotation_delay(&env), 100);
+
+        write_key_rotation_delay(&env, 0);
+        assert_eq!(read_key_rotation_delay(&env), 100);
+        assert!(read_pending_key_rotation_delay(&env).is_some());
+    });
+
+    advance_ledgers(&env, 99);
+    env.as_contract(&contract_id, || {
+        assert_eq!(read_key_rotation_delay(&env), 100);
+    });
+    advance_ledgers(&env, 1);
+    env.as_contract(&contract_id, || {
+        assert_eq!(read_key_rotation_delay(&env), 0);
+
+        // Raising applies at once and drops whatever decrease was queued
+        write_key_rotation_delay(&env, 50);
+        write_key_rotation_delay(&env, 10);
+        write_key_rotation_delay(&env, 200);
+        assert_eq!(read_key_rotation_delay(&env), 200);
+        assert!(read_pending_key_rotation_delay(&env).is_none());
+    });
+}
+
+#[test]
+fn test_applied_rotation_drops_the_pending_one_and_moves_nonces_on() {
+    let env = Env::default();
+    let secret_keys = secret_keys();
+    let contract_id = setup(&env, &secret_keys, 3);
+    let (new_pubkeys, new_pops) = bls_key_set(&env, &new_secret_keys());
+    let (old_pubkeys, old_pops) = bls_key_set(&env, &secret_keys);
+
+    env.as_contract(&contract_id, || {
+        write_key_rotation_delay(&env, 100);
+        queue_key_rotation(&env, new_pubkeys.clone(), new_pops.clone(), 2).unwrap();
+        write_key_rotation_delay(&env, 0);
+    });
+    advance_ledgers(&env, 100);
+
+    env.as_contract(&contract_id, || {
+        // Rotating to the same key set applies straight away with the delay at 0
+        queue_key_rotation(&env, old_pubkeys.clone(), old_pops, 3).unwrap();
+        assert_eq!(read_bls_pubkeys(&env), old_pubkeys);
+        assert!(read_pending_key_rotation(&env).is_none());
+        assert_eq!(
+            execute_pending_key_rotation(&env),
+            Err(ContractError::NoPendingKeyRotation)
+        );
+        assert_eq!(read_nonce(&env, 0), 1);
+        assert_eq!(read_nonce(&env, 15), 1);
+    });
+}
+
+#[test]
+fn test_dual_control_covers_request_unfreeze() {
+    let env = Env::default();
+    let (_, client) = setup_with_user_account(&env);
+
+    client.set_dual_control(&true, &None);
+    client.freeze(&None);
+    assert_eq!(
+        client.try_request_unfreeze(&None),
+        Err(Ok(ContractError::DualControlRequired))
+    );
+    assert_eq!(
+        client.try_update_allowance_expiration(&100, &None),
+        Err(Ok(ContractError::DualControlRequired))
+    );
+}
+
+#[test]
+fn test_legacy_passkey_migrates_into_the_registry_once() {
+    let env = Env::default();
+    let (contract_id, client) = setup_with_user_account(&env);
+
+    env.as_contract(&contract_id, || {
+        env.storage()
+            .persistent()
+            .set(&DataKey::WebKey, &cose_web_pubkey(&env));
+    });
+
+    let credential_id = Bytes::from_slice(&env, b"legacy-credential");
+    let platform = String::from_str(&env, "ios");
+    client.migrate_web_key(&credential_id, &platform, &None);
+
+    let migrated = client
+        .get_web_keys()
+        .iter()
+        .find(|key| key.credential_id == credential_id)
+        .unwrap();
+    assert_eq!(migrated.web_public_key, cose_web_pubkey(&env));
+    assert_eq!(migrated.platform, platform);
+    assert_eq!(
+        client.try_migrate_web_key(&credential_id, &platform, &None),
+        Err(Ok(ContractError::WebKeyNotFound))
+    );
+}
+
+fn register_token(env: &Env, holder: &Address, amount: i128) -> Address {
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(env))
+        .address();
+    StellarAssetClient::new(env, &token_id).mint(holder, &amount);
+    token_id
+}
+
+#[test]
+fn test_hidden_tokens_drop_out_of_every_token_read() {
+    let env = Env::default();
+    let (contract_id, client) = setup_with_user_account(&env);
+
+    let shown = register_token(&env, &contract_id, 10);
+    let hidden = register_token(&env, &contract_id, 20);
+    client.add_token(&shown, &None);
+    client.add_token(&hidden, &None);
+    client.set_token_hidden(&hidden, &true, &None);
+
+    let portfolio = client.get_portfolio(&0, &10, &true, &false);
+    assert_eq!(portfolio.total, 1);
+    assert_eq!(portfolio.entries.len(), 1);
+    assert_eq!(portfolio.entries.get_unchecked(0).token, shown);
+    assert_eq!(client.get_portfolio(&0, &10, &true, &true).total, 2);
+
+    let token_list = client.get_token_list();
+    assert!(token_list.contains_key(shown.clone()));
+    assert!(!token_list.contains_key(hidden.clone()));
+
+    client.set_token_hidden(&hidden, &false, &None);
+    assert_eq!(client.get_portfolio(&0, &10, &true, &false).total, 2);
+    assert!(client.get_token_list().contains_key(hidden));
+}
+
+#[test]
+fn test_deposit_lists_without_fetching_metadata_and_backfill_caches_it() {
+    let env = Env::default();
+    let (contract_id, client) = setup_with_user_account(&env);
+
+    let depositor = Address::generate(&env);
+    let token_id = register_token(&env, &depositor, 50);
+    client.deposit(&depositor, &token_id, &50);
+
+    let has_metadata = || {
+        env.as_contract(&contract_id, || {
+            env.storage()
+                .persistent()
+                .has(&DataKey::TokenMetadata(token_id.clone()))
+        })
+    };
+    assert!(client.get_token_list().contains_key(token_id.clone()));
+    assert!(!has_metadata());
+
+    assert_eq!(client.backfill_token_metadata(&0, &10), 1);
+    assert!(has_metadata());
+    assert_eq!(client.backfill_token_metadata(&0, &10), 0);
+}
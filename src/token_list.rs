@@ -1,10 +1,22 @@
 use soroban_sdk::{
-    contract, contractimpl, contracttype, symbol_short, Address, Env, FromVal, Map, Symbol, Val,
-    Vec,
+    contract, contractimpl, contracttype, symbol_short, token, Address, Env, FromVal, Map, Symbol,
+    Val, Vec,
 };
 
-use crate::data::DataKey;
+use crate::{
+    account_token::read_balance,
+    data::DataKey,
+    error::ContractError,
+    types::{Portfolio, PortfolioEntry, TokenMetadata},
+};
+
+// Upper bound on a portfolio page, each entry costs a balance call
+const MAX_PORTFOLIO_PAGE: u32 = 50;
+// Upper bound on a metadata backfill, each token costs three calls
+const MAX_BACKFILL_BATCH: u32 = 20;
 
+// Listing stays storage only, deposit and swaps call it for tokens the caller picks.
+// Metadata is cached by add_token or the backfill.
 pub fn write_add_token(env: Env, token: Address) {
     let default_tokens: Map<Address, ()> = Map::new(&env);
     let key = DataKey::TokenList;
@@ -18,7 +30,7 @@ pub fn write_add_token(env: Env, token: Address) {
     env.storage().persistent().set(&key, &tokens);
 }
 
-pub fn write_remove_token(env: Env, token: Address) {
+pub fn write_remove_token(env: Env, token: Address) -> Result<(), ContractError> {
     let default_tokens: Map<Address, ()> = Map::new(&env);
     let key = DataKey::TokenList;
     let mut tokens: Map<Address, ()> = env
@@ -26,8 +38,97 @@ pub fn write_remove_token(env: Env, token: Address) {
         .persistent()
         .get(&key)
         .unwrap_or(default_tokens);
-    tokens.remove(token);
+    if tokens.remove(token.clone()).is_none() {
+        return Err(ContractError::TokenNotListed);
+    }
     env.storage().persistent().set(&key, &tokens);
+    env.storage()
+        .persistent()
+        .remove(&DataKey::TokenMetadata(token.clone()));
+
+    let mut hidden_tokens = read_hidden_tokens(&env);
+    if hidden_tokens.remove(token).is_some() {
+        write_hidden_tokens(&env, &hidden_tokens);
+    }
+    Ok(())
+}
+
+fn fetch_token_metadata(env: &Env, token: &Address) -> TokenMetadata {
+    let client = token::Client::new(env, token);
+    TokenMetadata {
+        symbol: client.symbol(),
+        name: client.name(),
+        decimals: client.decimals(),
+        added_ledger: env.ledger().sequence(),
+    }
+}
+
+// Caches the token's metadata unless it already is, so reads don't call the token contract.
+// Returns whether it was written.
+pub fn write_token_metadata(env: &Env, token: &Address) -> bool {
+    let key = DataKey::TokenMetadata(token.clone());
+    if env.storage().persistent().has(&key) {
+        return false;
+    }
+    let metadata = fetch_token_metadata(env, token);
+    env.storage().persistent().set(&key, &metadata);
+    true
+}
+
+// Caches metadata for listed tokens that don't have it yet, open to anyone since it
+// only copies what the token contracts report. Returns how many entries were written.
+pub fn write_backfill_token_metadata(env: &Env, offset: u32, limit: u32) -> u32 {
+    let limit = limit.min(MAX_BACKFILL_BATCH);
+    let token_list = read_token_list(env.clone());
+
+    let mut written: u32 = 0;
+    for token_id in token_list.iter().skip(offset as usize).take(limit as usize) {
+        if write_token_metadata(env, &token_id) {
+            written += 1;
+        }
+    }
+
+    env.events().publish(
+        (Symbol::new(env, "token_list"), symbol_short!("backfill")),
+        written,
+    );
+    written
+}
+
+// Tokens listed without cached metadata fall back to a live lookup until backfilled
+pub fn read_token_metadata(env: &Env, token: &Address) -> TokenMetadata {
+    env.storage()
+        .persistent()
+        .get(&DataKey::TokenMetadata(token.clone()))
+        .unwrap_or_else(|| fetch_token_metadata(env, token))
+}
+
+pub fn write_token_hidden(env: &Env, token: &Address, hidden: bool) -> Result<(), ContractError> {
+    if !read_token_list(env.clone()).contains(token) {
+        return Err(ContractError::TokenNotListed);
+    }
+    let mut hidden_tokens = read_hidden_tokens(env);
+    if hidden {
+        hidden_tokens.set(token.clone(), ());
+    } else {
+        hidden_tokens.remove(token.clone());
+    }
+    write_hidden_tokens(env, &hidden_tokens);
+    Ok(())
+}
+
+// Tokens kept in the list but left out of token list reads
+pub fn read_hidden_tokens(env: &Env) -> Map<Address, ()> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::HiddenTokens)
+        .unwrap_or(Map::new(env))
+}
+
+fn write_hidden_tokens(env: &Env, hidden_tokens: &Map<Address, ()>) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::HiddenTokens, hidden_tokens);
 }
 
 pub fn read_token_list(env: Env) -> Vec<Address> {
@@ -38,3 +139,69 @@ pub fn read_token_list(env: Env) -> Vec<Address> {
         .unwrap_or(default_tokens)
         .keys()
 }
+
+// Ledger of the last transfer or swap that moved the token in or out of the wallet
+pub fn write_token_activity(env: &Env, token: &Address) {
+    env.storage().persistent().set(
+        &DataKey::TokenActivity(token.clone()),
+        &env.ledger().sequence(),
+    );
+}
+
+pub fn read_token_activity(env: &Env, token: &Address) -> u32 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::TokenActivity(token.clone()))
+        .unwrap_or(0)
+}
+
+// Pages over the token list after the hidden filter, which only reads the hidden index, so
+// `total` costs no calls. Zero balances are dropped within the page since finding them
+// takes a balance call, a page can therefore hold fewer than `limit` entries.
+// Metadata is only read for the entries returned.
+pub fn read_portfolio(
+    env: &Env,
+    offset: u32,
+    limit: u32,
+    include_zero: bool,
+    include_hidden: bool,
+) -> Portfolio {
+    let limit = limit.min(MAX_PORTFOLIO_PAGE);
+    let token_list = read_token_list(env.clone());
+    let hidden_tokens = read_hidden_tokens(env);
+    let total = if include_hidden {
+        token_list.len()
+    } else {
+        token_list.len() - hidden_tokens.len()
+    };
+
+    let mut entries = Vec::new(env);
+    let mut position: u32 = 0;
+    for token_id in token_list.iter() {
+        if !include_hidden && hidden_tokens.contains_key(token_id.clone()) {
+            continue;
+        }
+        position += 1;
+        if position <= offset {
+            continue;
+        }
+        if position > offset.saturating_add(limit) {
+            break;
+        }
+
+        let balance = read_balance(env, &token_id);
+        if balance == 0 && !include_zero {
+            continue;
+        }
+        let metadata = read_token_metadata(env, &token_id);
+        entries.push_back(PortfolioEntry {
+            last_activity_ledger: read_token_activity(env, &token_id),
+            token: token_id,
+            symbol: metadata.symbol,
+            decimals: metadata.decimals,
+            balance,
+        });
+    }
+
+    Portfolio { entries, total }
+}
@@ -1,3 +1,347 @@
+use soroban_sdk::{contracttype, Address, Bytes, BytesN, Map, String, Symbol, Vec};
+
 soroban_sdk::contractimport!(
     file = "../global_types/target/wasm32-unknown-unknown/release/socketfi_global_types.wasm"
 );
+
+#[derive(Clone)]
+#[contracttype]
+pub struct BlsSignature {
+    // Bit i set means the i-th registered pubkey took part in the aggregate
+    pub signers: u32,
+    pub signature: BytesN<192>,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct WebAuthnSignature {
+    // Selects which registered passkey produced the assertion
+    pub credential_id: Bytes,
+    pub authenticator_data: Bytes,
+    pub client_data_json: Bytes,
+    // Compact r || s, normalized to low-S
+    pub signature: BytesN<64>,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct PasskeyDetails {
+    pub credential_id: Bytes,
+    // COSE_Key encoded ES256 public key
+    pub web_public_key: BytesN<77>,
+    pub label: String,
+    pub platform: String,
+    pub created_ledger: u32,
+    // 0 until the passkey authorizes its first operation
+    pub last_used_ledger: u32,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct SessionSignature {
+    pub public_key: BytesN<32>,
+    pub signature: BytesN<64>,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct SessionKey {
+    pub public_key: BytesN<32>,
+    // Last ledger at which the session can sign
+    pub expiry_ledger: u32,
+    pub allowed_fns: Vec<Symbol>,
+    pub allowed_tokens: Vec<Address>,
+    // Cumulative amount the session may move, summed across tokens
+    pub spend_cap: i128,
+    pub spent: i128,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub enum SignerProof {
+    Bls(BlsSignature),
+    WebAuthn(WebAuthnSignature),
+    Session(SessionSignature),
+    Ed25519(Ed25519Signature),
+    Secp256k1(Secp256k1Signature),
+    // The linked external account authorizes the invocation itself
+    Account,
+    Guardian(GuardianSignature),
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct GuardianSignature {
+    pub index: u32,
+    pub proof: GuardianProof,
+}
+
+#[derive(Clone, Copy)]
+#[contracttype]
+#[repr(u32)]
+pub enum ValidityKind {
+    Ledger = 0,
+    Timestamp = 1,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct ValidityWindow {
+    pub kind: ValidityKind,
+    // Inclusive bounds, ledger sequence or unix seconds depending on kind
+    pub valid_after: u64,
+    pub valid_until: u64,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct TxSignature {
+    // Nonce channel the payload was built against
+    pub channel: u32,
+    pub validity: ValidityWindow,
+    // All proofs sign the same payload, functions with an auth policy may need several
+    pub proofs: Vec<SignerProof>,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct PendingKeyRotation {
+    pub pubkeys: Vec<BytesN<96>>,
+    pub threshold: u32,
+    // First ledger at which the rotation can be executed
+    pub execute_after: u32,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct TokenLimit {
+    pub per_tx: i128,
+    pub per_window: i128,
+    // Length of the rolling window, e.g. 17280 ledgers for ~24h
+    pub window_ledgers: u32,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct TokenSpendWindow {
+    // Amount spent per bucket, keyed by the bucket's first ledger
+    pub buckets: Map<u32, i128>,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub enum Guardian {
+    Account(Address),
+    Bls(BytesN<96>),
+    // COSE_Key encoded ES256 public key
+    Passkey(BytesN<77>),
+}
+
+// Contract types cannot box the passkey assertion
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone)]
+#[contracttype]
+pub enum GuardianProof {
+    // The guardian address authorizes the invocation itself
+    Account,
+    Bls(BytesN<192>),
+    Passkey(WebAuthnSignature),
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct GuardianConfig {
+    pub guardians: Vec<Guardian>,
+    pub threshold: u32,
+    // Ledgers between the start of a recovery and the earliest finalization
+    pub recovery_delay: u32,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct RecoveryRequest {
+    pub new_pubkeys: Vec<BytesN<96>>,
+    pub threshold: u32,
+    // Indexes into GuardianConfig::guardians
+    pub approvals: Vec<u32>,
+    pub initiated_ledger: u32,
+    pub execute_after: u32,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct LegacyDst {
+    pub version: u32,
+    pub dst: Bytes,
+    // Last ledger at which signatures under this DST are accepted
+    pub expiry_ledger: u32,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[contracttype]
+pub enum SignerKind {
+    Bls,
+    Passkey,
+    Ed25519,
+    Secp256k1,
+    Account,
+    // Met once the guardian threshold has signed
+    Guardian,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct PolicyGroup {
+    pub signers: Vec<SignerKind>,
+    // How many of the listed kinds must sign, 1 is any of them and signers.len() all of them
+    pub threshold: u32,
+}
+
+// Groups combine like the kinds inside them, e.g. BLS and (passkey or account) is a
+// [Bls] group and a [Passkey, Account] group, each with threshold 1, under threshold 2
+#[derive(Clone)]
+#[contracttype]
+pub struct AuthPolicy {
+    pub groups: Vec<PolicyGroup>,
+    // How many of the groups must be met
+    pub threshold: u32,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub enum AdminChange {
+    MasterContract(Address),
+    DappRouter(Address),
+    UserAccount(Address),
+    Upgrade(BytesN<32>),
+    // Lowering the admin change delay is itself timelocked
+    Delay(u32),
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct PendingAdminChange {
+    pub id: u32,
+    pub change: AdminChange,
+    pub execute_after: u32,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct FreezeState {
+    pub frozen: bool,
+    // Advances on every unfreeze, guardian freeze signatures commit to it
+    pub epoch: u32,
+    pub frozen_ledger: u32,
+    pub unfreeze_after: Option<u32>,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct Ed25519Signature {
+    pub public_key: BytesN<32>,
+    pub signature: BytesN<64>,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct Ed25519Signer {
+    pub public_key: BytesN<32>,
+    pub label: String,
+    pub created_ledger: u32,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct Secp256k1Signature {
+    // Ethereum address of the signing key
+    pub address: BytesN<20>,
+    // Compact r || s
+    pub signature: BytesN<64>,
+    // 0/1, or 27/28 as EVM wallets report v
+    pub recovery_id: u32,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct EvmSigner {
+    pub address: BytesN<20>,
+    pub label: String,
+    pub created_ledger: u32,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub enum BatchOp {
+    // to, token_id, amount
+    Withdraw(Address, Address, i128),
+    // token_id, spender, amount
+    Approve(Address, Address, i128),
+    // amount_in, amount_out_min, path, deadline
+    SwapSoroswap(i128, i128, Vec<Address>, u64),
+    // swaps_chain, token_in, in_amount, out_min
+    SwapAqua(
+        Vec<(Vec<Address>, BytesN<32>, Address)>,
+        Address,
+        u128,
+        u128,
+    ),
+    AddToken(Address),
+    RemoveToken(Address),
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub enum BatchResult {
+    Done,
+    // Amount of the output token the wallet received
+    Swapped(i128),
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct AddressBookEntry {
+    pub address: Address,
+    pub label: String,
+    // Only trusted entries can receive funds while allowlist mode is on
+    pub trusted: bool,
+    pub added_ledger: u32,
+    pub usable_after: u32,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct TokenMetadata {
+    pub symbol: String,
+    pub name: String,
+    pub decimals: u32,
+    pub added_ledger: u32,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct PortfolioEntry {
+    pub token: Address,
+    pub symbol: String,
+    pub decimals: u32,
+    pub balance: i128,
+    // 0 when the wallet hasn't moved the token since activity tracking began
+    pub last_activity_ledger: u32,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct Portfolio {
+    pub entries: Vec<PortfolioEntry>,
+    // Tokens matching the hidden filter, zero balances included whatever include_zero
+    // says. Pages are cut from this count, so offset + limit < total means more pages
+    // follow even when `entries` came back short.
+    pub total: u32,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct PendingDelayChange {
+    pub delay_ledgers: u32,
+    pub execute_after: u32,
+}
@@ -1,17 +1,27 @@
-use soroban_sdk::{Address, BytesN, Env, Map, String, Vec};
+use soroban_sdk::{
+    auth::InvokerContractAuthEntry, Address, Bytes, BytesN, Env, Map, String, Symbol, Val, Vec,
+};
 
 use crate::{
     error::ContractError,
-    types::{AllowanceDetails, TokenDetails, WebKeyDetails},
+    types::{
+        AddressBookEntry, AllowanceDetails, AuthPolicy, BatchOp, BatchResult, Ed25519Signer,
+        EvmSigner, FreezeState, Guardian, GuardianConfig, GuardianProof, PasskeyDetails,
+        PendingAdminChange, PendingKeyRotation, Portfolio, RecoveryRequest, SessionKey,
+        TokenDetails, TokenLimit, TokenMetadata, TxSignature,
+    },
 };
 
 pub trait AccountTrait {
     fn init(
         env: Env,
         bls_pubkeys: Vec<BytesN<96>>,
+        bls_pops: Vec<BytesN<192>>,
+        bls_threshold: u32,
         platform: String,
         social_username: String,
         web_pubkey: BytesN<77>,
+        web_credential_id: Bytes,
         master_contract_id: Address,
         dapp_router_contract_id: Address,
     ) -> Result<(), ContractError>;
@@ -19,23 +29,23 @@ pub trait AccountTrait {
     fn update_allowance_expiration(
         env: Env,
         expiration_ledger: u32,
-        tx_signature: Option<BytesN<192>>,
+        tx_signature: Option<TxSignature>,
     ) -> Result<(), ContractError>;
 
     fn set_user_account(
         env: Env,
         user_account_id: Address,
-        tx_signature: Option<BytesN<192>>,
+        tx_signature: Option<TxSignature>,
     ) -> Result<(), ContractError>;
     fn update_master_contract(
         env: Env,
         master_contract_id: Address,
-        tx_signature: Option<BytesN<192>>,
+        tx_signature: Option<TxSignature>,
     ) -> Result<(), ContractError>;
     fn update_dapp_router_contract(
         env: Env,
         dapp_router_contract_id: Address,
-        tx_signature: Option<BytesN<192>>,
+        tx_signature: Option<TxSignature>,
     ) -> Result<(), ContractError>;
     fn deposit(e: Env, from: Address, token_id: Address, amount: i128)
         -> Result<(), ContractError>;
@@ -44,7 +54,7 @@ pub trait AccountTrait {
         to: Address,
         token_id: Address,
         amount: i128,
-        tx_signature: Option<BytesN<192>>,
+        tx_signature: Option<TxSignature>,
     ) -> Result<(), ContractError>;
 
     fn swap_tokens_soroswap(
@@ -53,7 +63,7 @@ pub trait AccountTrait {
         amount_out_min: i128,
         path: Vec<Address>,
         deadline: u64,
-        tx_signature: Option<BytesN<192>>,
+        tx_signature: Option<TxSignature>,
     ) -> Result<(), ContractError>;
     fn swap_tokens_aqua(
         env: Env,
@@ -61,7 +71,7 @@ pub trait AccountTrait {
         token_in: Address,
         in_amount: u128,
         out_min: u128,
-        tx_signature: Option<BytesN<192>>,
+        tx_signature: Option<TxSignature>,
     ) -> Result<(), ContractError>;
     // fn reset_nonce(env: Env);
     fn approve(
@@ -69,8 +79,21 @@ pub trait AccountTrait {
         token_id: Address,
         spender: Address,
         amount: i128,
-        tx_signature: Option<BytesN<192>>,
+        tx_signature: Option<TxSignature>,
     ) -> Result<(), ContractError>;
+    fn execute_batch(
+        env: Env,
+        ops: Vec<BatchOp>,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<Vec<BatchResult>, ContractError>;
+    fn invoke(
+        env: Env,
+        contract: Address,
+        fn_name: Symbol,
+        args: Vec<Val>,
+        auth_entries: Vec<InvokerContractAuthEntry>,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<Val, ContractError>;
     fn spend(
         env: Env,
         token_id: Address,
@@ -78,18 +101,225 @@ pub trait AccountTrait {
         amount: i128,
         to: Address,
     ) -> Result<(), ContractError>;
+    fn set_token_limit(
+        env: Env,
+        token_id: Address,
+        per_tx: i128,
+        per_window: i128,
+        window_ledgers: u32,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError>;
+    fn remove_token_limit(
+        env: Env,
+        token_id: Address,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError>;
+    fn add_token(
+        env: Env,
+        token_id: Address,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError>;
+    fn remove_token(
+        env: Env,
+        token_id: Address,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError>;
+    fn set_token_hidden(
+        env: Env,
+        token_id: Address,
+        hidden: bool,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError>;
+    fn get_token_metadata(env: Env, token_id: Address) -> TokenMetadata;
+    fn backfill_token_metadata(env: Env, offset: u32, limit: u32) -> u32;
     fn get_token_list(env: Env) -> Result<Map<Address, TokenDetails>, ContractError>;
+    fn get_portfolio(
+        env: Env,
+        offset: u32,
+        limit: u32,
+        include_zero: bool,
+        include_hidden: bool,
+    ) -> Portfolio;
     fn get_spender_allowances(
         env: Env,
         spender: Address,
     ) -> Result<Map<Address, AllowanceDetails>, ContractError>;
-    fn get_web_keys(env: Env) -> WebKeyDetails;
+    fn add_web_key(
+        env: Env,
+        credential_id: Bytes,
+        web_pubkey: BytesN<77>,
+        label: String,
+        platform: String,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError>;
+    fn remove_web_key(
+        env: Env,
+        credential_id: Bytes,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError>;
+    fn migrate_web_key(
+        env: Env,
+        credential_id: Bytes,
+        platform: String,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError>;
+    fn get_web_keys(env: Env) -> Vec<PasskeyDetails>;
+    fn add_ed25519_signer(
+        env: Env,
+        public_key: BytesN<32>,
+        label: String,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError>;
+    fn remove_ed25519_signer(
+        env: Env,
+        public_key: BytesN<32>,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError>;
+    fn get_ed25519_signers(env: Env) -> Vec<Ed25519Signer>;
+    fn add_evm_signer(
+        env: Env,
+        address: BytesN<20>,
+        label: String,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError>;
+    fn remove_evm_signer(
+        env: Env,
+        address: BytesN<20>,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError>;
+    fn get_evm_signers(env: Env) -> Vec<EvmSigner>;
+    fn set_address_book_entry(
+        env: Env,
+        address: Address,
+        label: String,
+        trusted: bool,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError>;
+    fn remove_address_book_entry(
+        env: Env,
+        address: Address,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError>;
+    fn get_address_book(env: Env) -> Vec<AddressBookEntry>;
+    fn set_allowlist_mode(
+        env: Env,
+        enabled: bool,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError>;
+    fn get_allowlist_mode(env: Env) -> bool;
+    fn get_social_profile(env: Env) -> String;
     fn get_allowance(env: Env, token_id: Address, spender: Address) -> i128;
-    fn get_nonce(env: Env) -> BytesN<32>;
+    fn get_nonce(env: Env, channel: u32) -> u64;
+    fn add_session_key(
+        env: Env,
+        public_key: BytesN<32>,
+        expiry_ledger: u32,
+        allowed_fns: Vec<Symbol>,
+        allowed_tokens: Vec<Address>,
+        spend_cap: i128,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError>;
+    fn revoke_session_key(
+        env: Env,
+        public_key: BytesN<32>,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError>;
+    fn get_session_keys(env: Env) -> Vec<SessionKey>;
+    fn rotate_bls_keys(
+        env: Env,
+        new_pubkeys: Vec<BytesN<96>>,
+        pops: Vec<BytesN<192>>,
+        threshold: u32,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError>;
+    fn execute_key_rotation(env: Env) -> Result<(), ContractError>;
+    fn cancel_key_rotation(
+        env: Env,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError>;
+    fn set_key_rotation_delay(
+        env: Env,
+        delay_ledgers: u32,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError>;
+    fn get_pending_key_rotation(env: Env) -> Option<PendingKeyRotation>;
+    fn set_guardians(
+        env: Env,
+        guardians: Vec<Guardian>,
+        threshold: u32,
+        recovery_delay: u32,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError>;
+    fn start_recovery(
+        env: Env,
+        guardian_index: u32,
+        proof: GuardianProof,
+        new_pubkeys: Vec<BytesN<96>>,
+        pops: Vec<BytesN<192>>,
+        threshold: u32,
+    ) -> Result<(), ContractError>;
+    fn approve_recovery(
+        env: Env,
+        guardian_index: u32,
+        proof: GuardianProof,
+    ) -> Result<(), ContractError>;
+    fn finalize_recovery(env: Env) -> Result<(), ContractError>;
+    fn cancel_recovery(env: Env, tx_signature: Option<TxSignature>) -> Result<(), ContractError>;
+    fn get_guardians(env: Env) -> Option<GuardianConfig>;
+    fn get_recovery(env: Env) -> Option<RecoveryRequest>;
+    fn migrate_dst(
+        env: Env,
+        grace_ledgers: u32,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError>;
+    fn get_dst(env: Env) -> Bytes;
+    fn set_auth_policy(
+        env: Env,
+        fn_name: Symbol,
+        policy: AuthPolicy,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError>;
+    fn remove_auth_policy(
+        env: Env,
+        fn_name: Symbol,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError>;
+    fn get_auth_policies(env: Env) -> Map<Symbol, AuthPolicy>;
+    fn set_dual_control(
+        env: Env,
+        enabled: bool,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError>;
+    fn get_dual_control(env: Env) -> bool;
+    fn execute_admin_change(env: Env, id: u32) -> Result<(), ContractError>;
+    fn cancel_admin_change(
+        env: Env,
+        id: u32,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError>;
+    fn set_admin_change_delay(
+        env: Env,
+        delay_ledgers: u32,
+        tx_signature: Option<TxSignature>,
+    ) -> Result<(), ContractError>;
+    fn get_admin_change_delay(env: Env) -> u32;
+    fn get_pending_changes(env: Env) -> Vec<PendingAdminChange>;
+    fn freeze(env: Env, tx_signature: Option<TxSignature>) -> Result<(), ContractError>;
+    fn guardian_freeze(
+        env: Env,
+        guardian_index: u32,
+        proof: GuardianProof,
+    ) -> Result<(), ContractError>;
+    fn request_unfreeze(env: Env, tx_signature: Option<TxSignature>) -> Result<(), ContractError>;
+    fn unfreeze(env: Env) -> Result<(), ContractError>;
+    fn get_freeze_state(env: Env) -> FreezeState;
+    fn get_bls_signers(env: Env) -> Vec<BytesN<96>>;
+    fn get_bls_threshold(env: Env) -> u32;
+    fn get_token_limit(env: Env, token_id: Address) -> Option<TokenLimit>;
     fn get_balance(env: Env, token_id: Address) -> i128;
     fn upgrade(
         e: Env,
         new_wasm_hash: BytesN<32>,
-        tx_signature: Option<BytesN<192>>,
+        tx_signature: Option<TxSignature>,
     ) -> Result<(), ContractError>;
 }
@@ -0,0 +1,143 @@
+use soroban_sdk::{BytesN, Env};
+
+use crate::{
+    access::{read_web_key, write_web_key_used},
+    error::ContractError,
+    types::WebAuthnSignature,
+};
+
+// Upper bound for the clientDataJSON copied out of host memory
+const CLIENT_DATA_MAX_LEN: usize = 1024;
+// authenticatorData = rpIdHash (32) || flags (1) || signCount (4) || ...
+const AUTH_DATA_MIN_LEN: u32 = 37;
+const AUTH_DATA_FLAGS_INDEX: u32 = 32;
+const FLAG_USER_PRESENT: u8 = 0x01;
+
+const CLIENT_DATA_TYPE_GET: &[u8] = b"\"type\":\"webauthn.get\"";
+const CLIENT_DATA_CHALLENGE: &[u8] = b"\"challenge\":\"";
+// A 32 byte challenge is 43 base64url characters without padding
+const CHALLENGE_B64_LEN: usize = 43;
+const BASE64_URL: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+pub(crate) fn base64url_encode_challenge(challenge: &[u8; 32]) -> [u8; CHALLENGE_B64_LEN] {
+    let mut encoded = [0u8; CHALLENGE_B64_LEN];
+    let mut out = 0;
+
+    for chunk in challenge.chunks(3) {
+        let b0 = chunk[0] as u32;
+        let b1 = *chunk.get(1).unwrap_or(&0) as u32;
+        let b2 = *chunk.get(2).unwrap_or(&0) as u32;
+        let n = (b0 << 16) | (b1 << 8) | b2;
+
+        // Only emit the characters backed by input bytes, there's no padding
+        for i in 0..=chunk.len() {
+            encoded[out] = BASE64_URL[((n >> (18 - 6 * i)) & 63) as usize];
+            out += 1;
+        }
+    }
+
+    encoded
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+// map(5), kty: EC2, alg: ES256, crv: P-256
+const COSE_ES256_HEADER: [u8; 7] = [0xa5, 0x01, 0x02, 0x03, 0x26, 0x20, 0x01];
+
+// Passkeys are registered as COSE_Key ES256 maps, the curve point sits at fixed offsets:
+// a5 01 02 03 26 20 01 21 58 20 <x:32> 22 58 20 <y:32>
+pub fn cose_to_sec1_pubkey(
+    env: &Env,
+    web_pubkey: &BytesN<77>,
+) -> Result<BytesN<65>, ContractError> {
+    let cose = web_pubkey.to_array();
+    if cose[..7] != COSE_ES256_HEADER
+        || cose[7..10] != [0x21, 0x58, 0x20]
+        || cose[42..45] != [0x22, 0x58, 0x20]
+    {
+        return Err(ContractError::InvalidWebKey);
+    }
+
+    let mut sec1 = [0u8; 65];
+    sec1[0] = 0x04;
+    sec1[1..33].copy_from_slice(&cose[10..42]);
+    sec1[33..65].copy_from_slice(&cose[45..77]);
+    Ok(BytesN::from_array(env, &sec1))
+}
+
+// Verifies an assertion from one of the wallet's registered passkeys
+pub fn verify_webauthn_signature(
+    env: &Env,
+    challenge: &BytesN<32>,
+    tx_signature: WebAuthnSignature,
+) -> Result<(), ContractError> {
+    let web_key =
+        read_web_key(env, &tx_signature.credential_id).ok_or(ContractError::WebKeyNotFound)?;
+    verify_webauthn_assertion(env, &web_key.web_public_key, challenge, &tx_signature)?;
+
+    write_web_key_used(env, tx_signature.credential_id);
+    Ok(())
+}
+
+// Verifies a passkey assertion whose clientDataJSON challenge is the base64url of `challenge`.
+// The signature is the compact, low-S r || s form of the authenticator's DER signature.
+pub fn verify_webauthn_assertion(
+    env: &Env,
+    web_pubkey: &BytesN<77>,
+    challenge: &BytesN<32>,
+    tx_signature: &WebAuthnSignature,
+) -> Result<(), ContractError> {
+    let public_key = cose_to_sec1_pubkey(env, web_pubkey)?;
+
+    let authenticator_data = tx_signature.authenticator_data.clone();
+    if authenticator_data.len() < AUTH_DATA_MIN_LEN {
+        return Err(ContractError::InvalidWebAuthnData);
+    }
+    if authenticator_data.get_unchecked(AUTH_DATA_FLAGS_INDEX) & FLAG_USER_PRESENT == 0 {
+        return Err(ContractError::UserNotPresent);
+    }
+
+    let client_data_len = tx_signature.client_data_json.len() as usize;
+    if client_data_len > CLIENT_DATA_MAX_LEN {
+        return Err(ContractError::InvalidWebAuthnData);
+    }
+    let mut client_data_buf = [0u8; CLIENT_DATA_MAX_LEN];
+    tx_signature
+        .client_data_json
+        .copy_into_slice(&mut client_data_buf[..client_data_len]);
+    let client_data = &client_data_buf[..client_data_len];
+
+    if find(client_data, CLIENT_DATA_TYPE_GET).is_none() {
+        return Err(ContractError::InvalidWebAuthnData);
+    }
+
+    let challenge_start = find(client_data, CLIENT_DATA_CHALLENGE)
+        .ok_or(ContractError::InvalidWebAuthnData)?
+        + CLIENT_DATA_CHALLENGE.len();
+    let challenge_end = challenge_start + CHALLENGE_B64_LEN;
+    let expected_challenge = base64url_encode_challenge(&challenge.to_array());
+    if client_data.get(challenge_start..challenge_end) != Some(&expected_challenge[..])
+        || client_data.get(challenge_end) != Some(&b'"')
+    {
+        return Err(ContractError::InvalidChallenge);
+    }
+
+    // The authenticator signs authenticatorData || sha256(clientDataJSON)
+    let mut signed_data = authenticator_data;
+    signed_data.append(
+        &env.crypto()
+            .sha256(&tx_signature.client_data_json)
+            .to_bytes()
+            .into(),
+    );
+    let digest = env.crypto().sha256(&signed_data);
+
+    // Traps the invocation if the signature doesn't match
+    env.crypto()
+        .secp256r1_verify(&public_key, &digest, &tx_signature.signature);
+    Ok(())
+}